@@ -0,0 +1,80 @@
+// 连续生成卡片 检查网格和材质的数量不会随卡片数量增长
+// cargo bench --bench card_spawn
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use card_test::card_mesh::CardMeshBuilder;
+use card_test::cards::{gen_put_card, CardMeshCache};
+use std::time::Instant;
+
+#[derive(Component)]
+struct BenchPlane;
+
+// 一副牌的数量
+const DECK_SIZE: usize = 60;
+const BATCHES: [usize; 4] = [1, 9, 20, 30];
+
+fn spawn_cards(
+    In((count, image)): In<(usize, Handle<Image>)>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<CardMeshCache>,
+) {
+    let mut card_fn = gen_put_card::<BenchPlane>(
+        &mut commands,
+        &mut materials,
+        &mut meshes,
+        &mut cache,
+        CardMeshBuilder::new(3. / 1.4, 3., 0.05, 0.01),
+    );
+    for i in 0..count {
+        card_fn(image.clone(), Transform::from_xyz(i as f32, 0., 0.));
+    }
+}
+
+fn asset_counts(app: &App) -> (usize, usize) {
+    let world = app.world();
+    (
+        world.resource::<Assets<Mesh>>().len(),
+        world.resource::<Assets<StandardMaterial>>().len(),
+    )
+}
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Image>()
+        .init_resource::<CardMeshCache>();
+    let image = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(Image::default());
+
+    assert_eq!(BATCHES.iter().sum::<usize>(), DECK_SIZE);
+    println!(
+        "{:>6} {:>8} {:>10} {:>12}",
+        "cards", "meshes", "materials", "batch time"
+    );
+    let mut spawned = 0;
+    let mut first = None;
+    for batch in BATCHES {
+        let start = Instant::now();
+        app.world_mut()
+            .run_system_once_with((batch, image.clone()), spawn_cards)
+            .expect("spawn cards");
+        let elapsed = start.elapsed();
+        spawned += batch;
+        let counts = asset_counts(&app);
+        println!(
+            "{:>6} {:>8} {:>10} {:>12?}",
+            spawned, counts.0, counts.1, elapsed
+        );
+        // 第一张卡之后资源数量不再变化
+        match first {
+            None => first = Some(counts),
+            Some(first) => assert_eq!(first, counts, "asset count grew with card count"),
+        }
+    }
+}
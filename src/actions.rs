@@ -0,0 +1,958 @@
+use crate::camera_controller::CameraController;
+use crate::locale::{parse_key_values, Localization, LocalizedText};
+use crate::theme::{
+    ButtonRole, FontRole, PanelRole, TextRole, ThemedButton, ThemedButtonText, ThemedPanel,
+    ThemedText, UiTheme,
+};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use std::path::Path;
+
+// 玩家自定义的按键 启动时读取 每次改键后写回
+pub const BINDINGS_PATH: &str = "input_bindings.cfg";
+
+// 游戏里所有可以改键的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    EndTurn,
+    Select,
+    Cancel,
+    InspectCard,
+    ToggleLog,
+    ToggleBindings,
+    ToggleFocus,
+    NavUp,
+    NavDown,
+    NavLeft,
+    NavRight,
+    CameraPreset1,
+    CameraPreset2,
+    CameraPreset3,
+    CameraPreset4,
+    ToggleFreecam,
+    FlyForward,
+    FlyBack,
+    FlyLeft,
+    FlyRight,
+    FlyUp,
+    FlyDown,
+    FlyRun,
+    ToggleCursorGrab,
+    AddToSelection,
+    DiscardSelected,
+    MoveSelectedToSafe,
+    DialogConfirm,
+    DialogCancel,
+}
+
+// 同一个键只在同时生效的操作之间算冲突
+// 自由相机打开时对局里的操作不生效 关掉时自由相机的操作不生效
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionContext {
+    // 任何时候都生效
+    Global,
+    // 对局中
+    Game,
+    // 自由相机
+    Freecam,
+}
+
+impl ActionContext {
+    pub fn overlaps(&self, other: ActionContext) -> bool {
+        *self == other || *self == ActionContext::Global || other == ActionContext::Global
+    }
+
+    pub fn is_active(&self, freecam: bool) -> bool {
+        match self {
+            ActionContext::Global => true,
+            ActionContext::Game => !freecam,
+            ActionContext::Freecam => freecam,
+        }
+    }
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 29] = [
+        InputAction::EndTurn,
+        InputAction::Select,
+        InputAction::Cancel,
+        InputAction::InspectCard,
+        InputAction::ToggleLog,
+        InputAction::ToggleBindings,
+        InputAction::ToggleFocus,
+        InputAction::NavUp,
+        InputAction::NavDown,
+        InputAction::NavLeft,
+        InputAction::NavRight,
+        InputAction::CameraPreset1,
+        InputAction::CameraPreset2,
+        InputAction::CameraPreset3,
+        InputAction::CameraPreset4,
+        InputAction::ToggleFreecam,
+        InputAction::FlyForward,
+        InputAction::FlyBack,
+        InputAction::FlyLeft,
+        InputAction::FlyRight,
+        InputAction::FlyUp,
+        InputAction::FlyDown,
+        InputAction::FlyRun,
+        InputAction::ToggleCursorGrab,
+        InputAction::AddToSelection,
+        InputAction::DiscardSelected,
+        InputAction::MoveSelectedToSafe,
+        InputAction::DialogConfirm,
+        InputAction::DialogCancel,
+    ];
+
+    // 配置文件里的名字 语言文件里是 action.<名字>
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputAction::EndTurn => "end_turn",
+            InputAction::Select => "select",
+            InputAction::Cancel => "cancel",
+            InputAction::InspectCard => "inspect_card",
+            InputAction::ToggleLog => "toggle_log",
+            InputAction::ToggleBindings => "toggle_bindings",
+            InputAction::ToggleFocus => "toggle_focus",
+            InputAction::NavUp => "nav_up",
+            InputAction::NavDown => "nav_down",
+            InputAction::NavLeft => "nav_left",
+            InputAction::NavRight => "nav_right",
+            InputAction::CameraPreset1 => "camera_preset_1",
+            InputAction::CameraPreset2 => "camera_preset_2",
+            InputAction::CameraPreset3 => "camera_preset_3",
+            InputAction::CameraPreset4 => "camera_preset_4",
+            InputAction::ToggleFreecam => "toggle_freecam",
+            InputAction::FlyForward => "fly_forward",
+            InputAction::FlyBack => "fly_back",
+            InputAction::FlyLeft => "fly_left",
+            InputAction::FlyRight => "fly_right",
+            InputAction::FlyUp => "fly_up",
+            InputAction::FlyDown => "fly_down",
+            InputAction::FlyRun => "fly_run",
+            InputAction::ToggleCursorGrab => "toggle_cursor_grab",
+            InputAction::AddToSelection => "add_to_selection",
+            InputAction::DiscardSelected => "discard_selected",
+            InputAction::MoveSelectedToSafe => "move_selected_to_safe",
+            InputAction::DialogConfirm => "dialog_confirm",
+            InputAction::DialogCancel => "dialog_cancel",
+        }
+    }
+
+    pub fn key(&self) -> String {
+        format!("action.{}", self.name())
+    }
+
+    pub fn from_name(name: &str) -> Option<InputAction> {
+        InputAction::ALL
+            .into_iter()
+            .find(|action| action.name() == name)
+    }
+
+    pub fn context(&self) -> ActionContext {
+        match self {
+            InputAction::ToggleBindings | InputAction::ToggleFreecam => ActionContext::Global,
+            InputAction::FlyForward
+            | InputAction::FlyBack
+            | InputAction::FlyLeft
+            | InputAction::FlyRight
+            | InputAction::FlyUp
+            | InputAction::FlyDown
+            | InputAction::FlyRun
+            | InputAction::ToggleCursorGrab => ActionContext::Freecam,
+            _ => ActionContext::Game,
+        }
+    }
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        use Binding::{Button, Key};
+        match self {
+            InputAction::EndTurn => vec![Key(KeyCode::KeyE), Button(GamepadButton::Start)],
+            InputAction::Select => vec![Key(KeyCode::Space), Button(GamepadButton::South)],
+            InputAction::Cancel => vec![Key(KeyCode::Backspace), Button(GamepadButton::East)],
+            InputAction::InspectCard => vec![Key(KeyCode::KeyI), Button(GamepadButton::North)],
+            InputAction::ToggleLog => vec![Key(KeyCode::KeyL), Button(GamepadButton::Select)],
+            InputAction::ToggleBindings => vec![Key(KeyCode::F2)],
+            InputAction::ToggleFocus => vec![Key(KeyCode::Tab)],
+            InputAction::NavUp => vec![Key(KeyCode::ArrowUp), Button(GamepadButton::DPadUp)],
+            InputAction::NavDown => vec![Key(KeyCode::ArrowDown), Button(GamepadButton::DPadDown)],
+            InputAction::NavLeft => vec![Key(KeyCode::ArrowLeft), Button(GamepadButton::DPadLeft)],
+            InputAction::NavRight => {
+                vec![Key(KeyCode::ArrowRight), Button(GamepadButton::DPadRight)]
+            }
+            InputAction::CameraPreset1 => vec![Key(KeyCode::Digit1)],
+            InputAction::CameraPreset2 => vec![Key(KeyCode::Digit2)],
+            InputAction::CameraPreset3 => vec![Key(KeyCode::Digit3)],
+            InputAction::CameraPreset4 => vec![Key(KeyCode::Digit4)],
+            InputAction::ToggleFreecam => vec![Key(KeyCode::F1)],
+            InputAction::FlyForward => vec![Key(KeyCode::KeyW)],
+            InputAction::FlyBack => vec![Key(KeyCode::KeyS)],
+            InputAction::FlyLeft => vec![Key(KeyCode::KeyA)],
+            InputAction::FlyRight => vec![Key(KeyCode::KeyD)],
+            InputAction::FlyUp => vec![Key(KeyCode::KeyE)],
+            InputAction::FlyDown => vec![Key(KeyCode::KeyQ)],
+            InputAction::FlyRun => vec![Key(KeyCode::ShiftLeft)],
+            InputAction::ToggleCursorGrab => vec![Key(KeyCode::KeyM)],
+            InputAction::AddToSelection => vec![Key(KeyCode::ShiftLeft), Key(KeyCode::ShiftRight)],
+            InputAction::DiscardSelected => vec![Key(KeyCode::Delete)],
+            InputAction::MoveSelectedToSafe => vec![Key(KeyCode::KeyV)],
+            InputAction::DialogConfirm => vec![Key(KeyCode::Enter)],
+            InputAction::DialogCancel => vec![Key(KeyCode::Escape)],
+        }
+    }
+}
+
+// 一个绑定 键盘的键或者手柄的按钮
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(GamepadButton),
+}
+
+// 可以绑定的键 配置文件里用它们的名字
+const BINDABLE_KEYS: [KeyCode; 82] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Delete,
+    KeyCode::Insert,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Quote,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Backslash,
+    KeyCode::Backquote,
+    KeyCode::CapsLock,
+    KeyCode::Numpad0,
+];
+
+const BINDABLE_BUTTONS: [GamepadButton; 19] = [
+    GamepadButton::South,
+    GamepadButton::East,
+    GamepadButton::North,
+    GamepadButton::West,
+    GamepadButton::C,
+    GamepadButton::Z,
+    GamepadButton::LeftTrigger,
+    GamepadButton::LeftTrigger2,
+    GamepadButton::RightTrigger,
+    GamepadButton::RightTrigger2,
+    GamepadButton::Select,
+    GamepadButton::Start,
+    GamepadButton::Mode,
+    GamepadButton::LeftThumb,
+    GamepadButton::RightThumb,
+    GamepadButton::DPadUp,
+    GamepadButton::DPadDown,
+    GamepadButton::DPadLeft,
+    GamepadButton::DPadRight,
+];
+
+impl Binding {
+    // 键盘的键直接用名字 手柄按钮加上 Gamepad: 前缀
+    pub fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Button(button) => format!("Gamepad:{:?}", button),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Binding> {
+        match name.strip_prefix("Gamepad:") {
+            Some(button) => BINDABLE_BUTTONS
+                .into_iter()
+                .find(|candidate| format!("{:?}", candidate) == button)
+                .map(Binding::Button),
+            None => BINDABLE_KEYS
+                .into_iter()
+                .find(|candidate| format!("{:?}", candidate) == name)
+                .map(Binding::Key),
+        }
+    }
+
+    pub fn is_key(&self) -> bool {
+        matches!(self, Binding::Key(_))
+    }
+}
+
+// 两个操作在同一个场景下用了同一个键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BindingConflict {
+    pub first: InputAction,
+    pub second: InputAction,
+    pub binding: Binding,
+}
+
+// 每个操作绑定的键 一个操作可以有多个键
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct InputBindings {
+    bindings: HashMap<InputAction, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            bindings: InputAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: InputAction) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn set(&mut self, action: InputAction, bindings: Vec<Binding>) {
+        self.bindings.insert(action, bindings);
+    }
+
+    // 替换同一类的第一个绑定 另一类的绑定保留
+    pub fn rebind(&mut self, action: InputAction, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        match bindings
+            .iter()
+            .position(|old| old.is_key() == binding.is_key())
+        {
+            Some(index) => bindings[index] = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    // 第一个键盘绑定 自由相机用它
+    pub fn first_key(&self, action: InputAction) -> Option<KeyCode> {
+        self.get(action).iter().find_map(|binding| match binding {
+            Binding::Key(key) => Some(*key),
+            Binding::Button(_) => None,
+        })
+    }
+
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        let mut conflicts = vec![];
+        for (i, first) in InputAction::ALL.iter().enumerate() {
+            for second in &InputAction::ALL[i + 1..] {
+                if !first.context().overlaps(second.context()) {
+                    continue;
+                }
+                for binding in self.get(*first) {
+                    if self.get(*second).contains(binding) {
+                        conflicts.push(BindingConflict {
+                            first: *first,
+                            second: *second,
+                            binding: *binding,
+                        });
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn is_conflicting(&self, action: InputAction) -> bool {
+        self.conflicts()
+            .iter()
+            .any(|conflict| conflict.first == action || conflict.second == action)
+    }
+
+    // action = KeyA, Gamepad:South 格式 没写的操作用默认值
+    pub fn parse(source: &str) -> Result<Self, std::io::Error> {
+        let mut bindings = Self::default();
+        for (name, value) in parse_key_values(source)? {
+            let Some(action) = InputAction::from_name(&name) else {
+                warn!("未知的操作 {}", name);
+                continue;
+            };
+            let mut list = vec![];
+            for binding in value.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                match Binding::from_name(binding) {
+                    Some(binding) => list.push(binding),
+                    None => warn!("未知的按键 {} = {}", name, binding),
+                }
+            }
+            bindings.set(action, list);
+        }
+        Ok(bindings)
+    }
+
+    pub fn to_config(&self) -> String {
+        let mut text = String::new();
+        for action in InputAction::ALL {
+            let names: Vec<String> = self.get(action).iter().map(Binding::name).collect();
+            text.push_str(&format!("{} = {}\n", action.name(), names.join(", ")));
+        }
+        text
+    }
+
+    // 文件不存在时用默认值
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source).unwrap_or_else(|err| {
+                error!("读取按键设置失败 {}: {}", path.display(), err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_config())
+    }
+}
+
+// 这一帧每个操作的状态 其他系统只看这里 不直接读键盘
+// press 可以在测试里模拟操作 下一帧生效
+#[derive(Resource, Debug, Default)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+    synthetic: Vec<InputAction>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn press(&mut self, action: InputAction) {
+        self.synthetic.push(action);
+    }
+}
+
+// 正在改键的操作 等待下一次按键
+#[derive(Resource, Debug, Default)]
+pub struct BindingCapture(pub Option<InputAction>);
+
+pub fn update_action_state(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    controllers: Query<&CameraController>,
+    bindings: Res<InputBindings>,
+    capture: Res<BindingCapture>,
+    mut state: ResMut<ActionState>,
+) {
+    let synthetic = std::mem::take(&mut state.synthetic);
+    state.pressed.clear();
+    state.just_pressed.clear();
+    // 改键时按下的键不触发操作
+    if capture.0.is_some() {
+        return;
+    }
+    let is_pressed = |binding: &Binding| match binding {
+        Binding::Key(key) => keyboard_input.pressed(*key),
+        Binding::Button(button) => gamepads.iter().any(|gamepad| gamepad.pressed(*button)),
+    };
+    let is_just_pressed = |binding: &Binding| match binding {
+        Binding::Key(key) => keyboard_input.just_pressed(*key),
+        Binding::Button(button) => gamepads.iter().any(|gamepad| gamepad.just_pressed(*button)),
+    };
+    let freecam = controllers.iter().any(|controller| controller.enabled);
+    for action in InputAction::ALL {
+        if !action.context().is_active(freecam) {
+            continue;
+        }
+        let list = bindings.get(action);
+        if list.iter().any(is_pressed) {
+            state.pressed.insert(action);
+        }
+        if list.iter().any(is_just_pressed) {
+            state.just_pressed.insert(action);
+        }
+    }
+    for action in synthetic {
+        state.pressed.insert(action);
+        state.just_pressed.insert(action);
+    }
+}
+
+// 自由相机沿用自己的按键字段 绑定变化时写进去
+pub fn sync_camera_controller_keys(
+    bindings: Res<InputBindings>,
+    mut controllers: Query<&mut CameraController>,
+) {
+    for mut controller in controllers.iter_mut() {
+        if !bindings.is_changed() && !controller.is_added() {
+            continue;
+        }
+        let controller = &mut *controller;
+        for (action, key) in [
+            (InputAction::FlyForward, &mut controller.key_forward),
+            (InputAction::FlyBack, &mut controller.key_back),
+            (InputAction::FlyLeft, &mut controller.key_left),
+            (InputAction::FlyRight, &mut controller.key_right),
+            (InputAction::FlyUp, &mut controller.key_up),
+            (InputAction::FlyDown, &mut controller.key_down),
+            (InputAction::FlyRun, &mut controller.key_run),
+            (
+                InputAction::ToggleCursorGrab,
+                &mut controller.keyboard_key_toggle_cursor_grab,
+            ),
+        ] {
+            if let Some(bound) = bindings.first_key(action) {
+                *key = bound;
+            }
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct BindingsPanel;
+
+#[derive(Component, Debug)]
+pub struct BindingButton(pub InputAction);
+
+#[derive(Component, Debug)]
+pub struct BindingText(pub InputAction);
+
+#[derive(Component, Debug)]
+pub struct BindingLabel(pub InputAction);
+
+#[derive(Component, Debug)]
+pub struct ResetBindingsButton;
+
+fn binding_text(action: InputAction, bindings: &InputBindings) -> String {
+    let names: Vec<String> = bindings.get(action).iter().map(Binding::name).collect();
+    names.join(" / ")
+}
+
+// 改键面板 默认隐藏 每个操作一行 点右边的按钮后按下新的键
+pub fn setup_bindings_panel(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    theme: Res<UiTheme>,
+    bindings: Res<InputBindings>,
+) {
+    let font = localization.font();
+    commands
+        .spawn((
+            BindingsPanel,
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                left: Val::Percent(50.0),
+                top: Val::Px(theme.spacing.large),
+                margin: UiRect::left(Val::Px(-240.0)),
+                width: Val::Px(480.0),
+                max_height: Val::Percent(85.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(theme.spacing.medium)),
+                row_gap: Val::Px(theme.spacing.small),
+                overflow: Overflow::scroll_y(),
+                ..default()
+            },
+            ThemedPanel(PanelRole::Dialog),
+            GlobalZIndex(90),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(localization.get("bindings.title")),
+                LocalizedText::new("bindings.title"),
+                TextFont {
+                    font: font.clone(),
+                    ..default()
+                },
+                ThemedText::new(FontRole::Heading, TextRole::Dark),
+            ));
+            for action in InputAction::ALL {
+                parent
+                    .spawn(Node {
+                        justify_content: JustifyContent::SpaceBetween,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(theme.spacing.medium),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            BindingLabel(action),
+                            Text::new(localization.get(&action.key())),
+                            LocalizedText::new(action.key()),
+                            TextFont {
+                                font: font.clone(),
+                                ..default()
+                            },
+                            ThemedText::new(FontRole::Small, TextRole::Dark),
+                        ));
+                        row.spawn((
+                            Button,
+                            BindingButton(action),
+                            ThemedButton::new(ButtonRole::Item),
+                            Node {
+                                min_width: Val::Px(180.0),
+                                padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((
+                                BindingText(action),
+                                Text::new(binding_text(action, &bindings)),
+                                TextFont {
+                                    font: font.clone(),
+                                    ..default()
+                                },
+                                ThemedButtonText(FontRole::Tiny),
+                                PickingBehavior::IGNORE,
+                            ));
+                        })
+                        .observe(on_binding_click);
+                    });
+            }
+            parent
+                .spawn((
+                    Button,
+                    ResetBindingsButton,
+                    ThemedButton::new(ButtonRole::Danger),
+                    Node {
+                        align_self: AlignSelf::End,
+                        padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                        ..default()
+                    },
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(localization.get("bindings.reset")),
+                        LocalizedText::new("bindings.reset"),
+                        TextFont {
+                            font: font.clone(),
+                            ..default()
+                        },
+                        ThemedButtonText(FontRole::Small),
+                        PickingBehavior::IGNORE,
+                    ));
+                })
+                .observe(on_reset_bindings_click);
+        });
+}
+
+fn on_binding_click(
+    click: Trigger<Pointer<Click>>,
+    buttons: Query<&BindingButton>,
+    mut capture: ResMut<BindingCapture>,
+) {
+    if let Ok(button) = buttons.get(click.entity()) {
+        capture.0 = Some(button.0);
+    }
+}
+
+fn on_reset_bindings_click(
+    _click: Trigger<Pointer<Click>>,
+    mut bindings: ResMut<InputBindings>,
+    mut capture: ResMut<BindingCapture>,
+) {
+    capture.0 = None;
+    *bindings = InputBindings::default();
+    save_bindings(&bindings);
+}
+
+fn save_bindings(bindings: &InputBindings) {
+    if let Err(err) = bindings.save(Path::new(BINDINGS_PATH)) {
+        error!("保存按键设置失败 {}: {}", BINDINGS_PATH, err);
+    }
+}
+
+// 等待改键时 下一次按下的键就是新的绑定 Escape 放弃
+pub fn capture_binding_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut capture: ResMut<BindingCapture>,
+    mut bindings: ResMut<InputBindings>,
+) {
+    let Some(action) = capture.0 else {
+        return;
+    };
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        capture.0 = None;
+        return;
+    }
+    let key = keyboard_input
+        .get_just_pressed()
+        .find(|key| BINDABLE_KEYS.contains(key))
+        .map(|key| Binding::Key(*key));
+    let button = gamepads
+        .iter()
+        .flat_map(|gamepad| gamepad.get_just_pressed())
+        .find(|button| BINDABLE_BUTTONS.contains(button))
+        .map(|button| Binding::Button(*button));
+    let Some(binding) = key.or(button) else {
+        return;
+    };
+    capture.0 = None;
+    bindings.rebind(action, binding);
+    for conflict in bindings.conflicts() {
+        if conflict.first == action || conflict.second == action {
+            warn!(
+                "按键冲突 {} 同时绑定了 {} 和 {}",
+                conflict.binding.name(),
+                conflict.first.name(),
+                conflict.second.name()
+            );
+        }
+    }
+    save_bindings(&bindings);
+}
+
+pub fn toggle_bindings_panel(
+    actions: Res<ActionState>,
+    mut capture: ResMut<BindingCapture>,
+    mut panels: Query<&mut Node, With<BindingsPanel>>,
+) {
+    if !actions.just_pressed(InputAction::ToggleBindings) {
+        return;
+    }
+    capture.0 = None;
+    for mut node in panels.iter_mut() {
+        node.display = match node.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+// 刷新每一行的按键 等待输入的一行高亮 冲突的操作名字标红
+pub fn refresh_bindings_panel(
+    localization: Res<Localization>,
+    bindings: Res<InputBindings>,
+    capture: Res<BindingCapture>,
+    mut texts: Query<(&BindingText, &mut Text)>,
+    mut buttons: Query<(&BindingButton, &mut ThemedButton)>,
+    mut labels: Query<(&BindingLabel, &mut ThemedText)>,
+) {
+    if !bindings.is_changed() && !capture.is_changed() && !localization.is_changed() {
+        return;
+    }
+    let conflicts = bindings.conflicts();
+    for (text, mut value) in texts.iter_mut() {
+        value.0 = if capture.0 == Some(text.0) {
+            localization.get("bindings.press_key").to_string()
+        } else {
+            binding_text(text.0, &bindings)
+        };
+    }
+    for (button, mut themed) in buttons.iter_mut() {
+        themed.selected = capture.0 == Some(button.0);
+    }
+    for (label, mut themed) in labels.iter_mut() {
+        let conflicting = conflicts
+            .iter()
+            .any(|conflict| conflict.first == label.0 || conflict.second == label.0);
+        themed.color = if conflicting {
+            TextRole::Error
+        } else {
+            TextRole::Dark
+        };
+    }
+}
+
+// 只用到镜头的场景没有界面相关的资源 不生成改键面板
+fn has_ui(theme: Option<Res<UiTheme>>, localization: Option<Res<Localization>>) -> bool {
+    theme.is_some() && localization.is_some()
+}
+
+// 镜头等模块单独使用时也会加上它 已经加过就跳过
+pub struct InputActionsPlugin;
+
+impl Plugin for InputActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputBindings::load(Path::new(BINDINGS_PATH)))
+            .init_resource::<ActionState>()
+            .init_resource::<BindingCapture>()
+            .add_systems(Startup, setup_bindings_panel.run_if(has_ui))
+            .add_systems(PreUpdate, update_action_state.after(InputSystem))
+            .add_systems(
+                Update,
+                (
+                    sync_camera_controller_keys,
+                    toggle_bindings_panel,
+                    capture_binding_system,
+                    refresh_bindings_panel.run_if(has_ui),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::InputPlugin;
+
+    #[test]
+    fn parse_reads_bindings_and_keeps_defaults() {
+        let source = "# 注释\n\
+            end_turn = KeyR, Gamepad:South\n\
+            select = KeyZ, NotAKey\n\
+            not_an_action = KeyA\n\
+            inspect_card =\n";
+        let bindings = InputBindings::parse(source).unwrap();
+        assert_eq!(
+            bindings.get(InputAction::EndTurn),
+            [
+                Binding::Key(KeyCode::KeyR),
+                Binding::Button(GamepadButton::South)
+            ]
+        );
+        assert_eq!(
+            bindings.get(InputAction::Select),
+            [Binding::Key(KeyCode::KeyZ)]
+        );
+        assert!(bindings.get(InputAction::InspectCard).is_empty());
+        // 没写的操作用默认值
+        assert_eq!(
+            bindings.get(InputAction::Cancel),
+            InputAction::Cancel.default_bindings()
+        );
+        assert!(InputBindings::parse("end_turn KeyR").is_err());
+    }
+
+    #[test]
+    fn to_config_round_trips() {
+        let mut bindings = InputBindings::default();
+        bindings.rebind(InputAction::EndTurn, Binding::Key(KeyCode::Enter));
+        bindings.rebind(
+            InputAction::Select,
+            Binding::Button(GamepadButton::RightTrigger),
+        );
+        bindings.set(InputAction::ToggleLog, vec![]);
+        let parsed = InputBindings::parse(&bindings.to_config()).unwrap();
+        assert_eq!(parsed, bindings);
+        assert_eq!(
+            InputBindings::parse(&InputBindings::default().to_config()).unwrap(),
+            InputBindings::default()
+        );
+    }
+
+    #[test]
+    fn conflicts_only_between_overlapping_contexts() {
+        assert!(InputBindings::default().conflicts().is_empty());
+
+        let mut bindings = InputBindings::default();
+        // 对局和自由相机不会同时生效 同一个键不算冲突
+        bindings.rebind(InputAction::DiscardSelected, Binding::Key(KeyCode::KeyW));
+        assert!(bindings.conflicts().is_empty());
+
+        bindings.rebind(InputAction::InspectCard, Binding::Key(KeyCode::KeyE));
+        assert_eq!(
+            bindings.conflicts(),
+            [BindingConflict {
+                first: InputAction::EndTurn,
+                second: InputAction::InspectCard,
+                binding: Binding::Key(KeyCode::KeyE),
+            }]
+        );
+        assert!(bindings.is_conflicting(InputAction::InspectCard));
+        assert!(!bindings.is_conflicting(InputAction::FlyUp));
+
+        // 全局的操作和每个场景都会冲突
+        bindings.rebind(InputAction::ToggleFreecam, Binding::Key(KeyCode::KeyQ));
+        assert!(bindings.is_conflicting(InputAction::FlyDown));
+    }
+
+    #[test]
+    fn freecam_blocks_game_actions() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .init_resource::<InputBindings>()
+            .init_resource::<ActionState>()
+            .init_resource::<BindingCapture>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem));
+        let camera = app
+            .world_mut()
+            .spawn(CameraController {
+                enabled: false,
+                ..default()
+            })
+            .id();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyE);
+        app.update();
+        let actions = app.world().resource::<ActionState>();
+        assert!(actions.pressed(InputAction::EndTurn));
+        assert!(!actions.pressed(InputAction::FlyUp));
+
+        app.world_mut()
+            .get_mut::<CameraController>(camera)
+            .unwrap()
+            .enabled = true;
+        app.update();
+        let actions = app.world().resource::<ActionState>();
+        assert!(!actions.pressed(InputAction::EndTurn));
+        assert!(actions.pressed(InputAction::FlyUp));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::settings::{AnimationCategory, AnimationCategoryExt};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tween::combinator::{
+    event, parallel, sequence, tween, AnimationCommands, TransformTargetStateExt,
+};
+use bevy_tween::interpolation::EaseKind;
+use bevy_tween::prelude::{AnimationBuilderExt, IntoTarget};
+use bevy_tween::tween::{AnimationTarget, TargetComponent};
+use std::f32::consts::PI;
+use std::time::Duration;
+
+// 卡片正反面的状态 true 为正面朝上
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaceUp(pub bool);
+
+impl Default for FaceUp {
+    fn default() -> Self {
+        FaceUp(true)
+    }
+}
+
+// 卡片被横置(消耗)
+#[derive(Component, Debug)]
+pub struct Tapped;
+
+// 翻面时抬起的高度
+const FLIP_LIFT: f32 = 1.0;
+// 重击前抬起的高度
+const SLAM_LIFT: f32 = 2.0;
+// 受击抖动的幅度
+const SHAKE_OFFSET: f32 = 0.15;
+
+// 翻面 绕卡片自身的 Y 轴转半圈 同时抬起再落下
+pub fn flip(
+    target: TargetComponent,
+    from: Transform,
+) -> impl FnOnce(&mut AnimationCommands, &mut Duration) {
+    let mut state = target.transform_state(from);
+    let mut lift_state = target.transform_state(from);
+    let end_rotation = from.rotation * Quat::from_axis_angle(Vec3::Y, PI);
+    let top = from.translation + Vec3::Z * FLIP_LIFT;
+    parallel((
+        tween(
+            Duration::from_secs_f32(0.5),
+            EaseKind::CubicInOut,
+            state.rotation_to(end_rotation),
+        ),
+        sequence((
+            tween(
+                Duration::from_secs_f32(0.25),
+                EaseKind::QuadraticOut,
+                lift_state.translation_to(top),
+            ),
+            tween(
+                Duration::from_secs_f32(0.25),
+                EaseKind::QuadraticIn,
+                lift_state.translation_to(from.translation),
+            ),
+        )),
+    ))
+}
+
+// 横置 绕桌面的 Z 轴顺时针转 90°
+pub fn tap(
+    target: TargetComponent,
+    from: Transform,
+) -> impl FnOnce(&mut AnimationCommands, &mut Duration) {
+    let mut state = target.transform_state(from);
+    let end_rotation = Quat::from_axis_angle(Vec3::Z, -PI / 2.0) * from.rotation;
+    tween(
+        Duration::from_secs_f32(0.3),
+        EaseKind::QuadraticOut,
+        state.rotation_to(end_rotation),
+    )
+}
+
+// 重置 横置的反向
+pub fn untap(
+    target: TargetComponent,
+    from: Transform,
+) -> impl FnOnce(&mut AnimationCommands, &mut Duration) {
+    let mut state = target.transform_state(from);
+    let end_rotation = Quat::from_axis_angle(Vec3::Z, PI / 2.0) * from.rotation;
+    tween(
+        Duration::from_secs_f32(0.3),
+        EaseKind::QuadraticOut,
+        state.rotation_to(end_rotation),
+    )
+}
+
+// 抬起放大 然后重重砸回原位 落地时发出 "slam" 事件
+pub fn lift_and_slam(
+    target: TargetComponent,
+    from: Transform,
+) -> impl FnOnce(&mut AnimationCommands, &mut Duration) {
+    let mut translation = target.transform_state(from);
+    let mut scale = target.transform_state(from);
+    let top = from.translation + Vec3::Z * SLAM_LIFT;
+    sequence((
+        parallel((
+            tween(
+                Duration::from_secs_f32(0.4),
+                EaseKind::QuadraticOut,
+                translation.translation_to(top),
+            ),
+            tween(
+                Duration::from_secs_f32(0.4),
+                EaseKind::QuadraticOut,
+                scale.scale_to(from.scale * 1.15),
+            ),
+        )),
+        parallel((
+            tween(
+                Duration::from_secs_f32(0.12),
+                EaseKind::QuadraticIn,
+                translation.translation_to(from.translation),
+            ),
+            tween(
+                Duration::from_secs_f32(0.12),
+                EaseKind::QuadraticIn,
+                scale.scale_to(from.scale),
+            ),
+        )),
+        event("slam"),
+    ))
+}
+
+// 受击抖动 左右来回晃动 幅度逐渐减小 最后回到原位
+pub fn shake(
+    target: TargetComponent,
+    from: Transform,
+) -> impl FnOnce(&mut AnimationCommands, &mut Duration) {
+    let mut state = target.transform_state(from);
+    let step = Duration::from_secs_f32(0.05);
+    let offset = |k: f32| from.translation + Vec3::X * SHAKE_OFFSET * k;
+    sequence((
+        tween(step, EaseKind::Linear, state.translation_to(offset(1.0))),
+        tween(step, EaseKind::Linear, state.translation_to(offset(-1.0))),
+        tween(step, EaseKind::Linear, state.translation_to(offset(0.6))),
+        tween(step, EaseKind::Linear, state.translation_to(offset(-0.6))),
+        tween(step, EaseKind::Linear, state.translation_to(offset(0.3))),
+        tween(
+            step,
+            EaseKind::QuadraticOut,
+            state.translation_to(from.translation),
+        ),
+    ))
+}
+
+// 执行时再看卡片的状态 已经是目标状态就什么都不做 和 Presentation::Flip 一样
+fn animate_on_change<'a, 'w>(
+    commands: &'a mut EntityCommands<'w>,
+    unchanged: impl FnOnce(&EntityWorldMut) -> bool + Send + 'static,
+    animate: impl FnOnce(&mut EntityCommands) + Send + 'static,
+) -> &'a mut EntityCommands<'w> {
+    commands.queue(move |mut entity: EntityWorldMut| {
+        if unchanged(&entity) {
+            return;
+        }
+        let id = entity.id();
+        entity.world_scope(|world| {
+            animate(&mut world.commands().entity(id));
+            world.flush();
+        });
+    })
+}
+
+// 在卡片实体上直接播放预设动画 同时维护 FaceUp/Tapped 状态
+pub trait CardAnimationExt {
+    fn flip_card(&mut self, from: &Transform, face_up: bool) -> &mut Self;
+    fn tap_card(&mut self, from: &Transform) -> &mut Self;
+    fn untap_card(&mut self, from: &Transform) -> &mut Self;
+    fn lift_and_slam_card(&mut self, from: &Transform) -> &mut Self;
+    fn shake_card(&mut self, from: &Transform) -> &mut Self;
+}
+
+impl CardAnimationExt for EntityCommands<'_> {
+    fn flip_card(&mut self, from: &Transform, face_up: bool) -> &mut Self {
+        let from = *from;
+        animate_on_change(
+            self,
+            move |entity| {
+                entity
+                    .get::<FaceUp>()
+                    .is_some_and(|current| current.0 == face_up)
+            },
+            move |entity| {
+                entity
+                    .insert(FaceUp(face_up))
+                    .animation()
+                    .category(AnimationCategory::Play)
+                    .insert(flip(AnimationTarget.into_target(), from));
+            },
+        )
+    }
+
+    fn tap_card(&mut self, from: &Transform) -> &mut Self {
+        let from = *from;
+        animate_on_change(
+            self,
+            |entity| entity.contains::<Tapped>(),
+            move |entity| {
+                entity
+                    .insert(Tapped)
+                    .animation()
+                    .category(AnimationCategory::Play)
+                    .insert(tap(AnimationTarget.into_target(), from));
+            },
+        )
+    }
+
+    fn untap_card(&mut self, from: &Transform) -> &mut Self {
+        let from = *from;
+        animate_on_change(
+            self,
+            |entity| !entity.contains::<Tapped>(),
+            move |entity| {
+                entity
+                    .remove::<Tapped>()
+                    .animation()
+                    .category(AnimationCategory::Play)
+                    .insert(untap(AnimationTarget.into_target(), from));
+            },
+        )
+    }
+
+    fn lift_and_slam_card(&mut self, from: &Transform) -> &mut Self {
+        self.animation()
+            .category(AnimationCategory::Combat)
+            .insert(lift_and_slam(AnimationTarget.into_target(), *from));
+        self
+    }
+
+    fn shake_card(&mut self, from: &Transform) -> &mut Self {
+        self.animation()
+            .category(AnimationCategory::Combat)
+            .insert(shake(AnimationTarget.into_target(), *from));
+        self
+    }
+}
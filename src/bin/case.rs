@@ -3,43 +3,48 @@ use bevy::app::App;
 use bevy::asset::{Assets, Handle};
 use bevy::color::Color;
 
-use bevy::math::{Quat, Vec2};
 use bevy::pbr::StandardMaterial;
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use card_test::camera_controller::{CameraController, CameraControllerPlugin};
-use card_test::cards::gen_put_card;
-use card_test::cases::{CaseImages, CasePlane, render_case};
-use std::f32::consts::PI;
+use card_test::camera_controller::CameraController;
+use card_test::board::{BoardConfig, CardPlane};
+use card_test::camera_rig::CameraRig;
+use card_test::cards::{gen_put_card, CardMeshCache, CardsConfig};
+use card_test::loading::{AssetManifest, LoadingPlugin, MatchState};
+use card_test::CardGamePlugins;
 
 fn main() {
     App::new()
         .add_plugins((
             DefaultPlugins,
-            MeshPickingPlugin,
-            // CameraControllerPlugin,
-            // 动画相关
+            CardGamePlugins.set(LoadingPlugin::new(
+                AssetManifest::default().with_card("NAAI-A-001"),
+            )),
         ))
         .add_plugins(WorldInspectorPlugin::new())
-        .add_systems(Startup, setup)
+        .add_systems(OnEnter(MatchState::InMatch), setup)
         .run();
 }
 
-#[derive(Component)]
-pub struct CardPlane;
-
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<CardMeshCache>,
     asset_server: Res<AssetServer>,
+    board: Res<BoardConfig>,
+    cards_config: Res<CardsConfig>,
 ) {
     // config_store.config_mut::<AabbGizmoConfigGroup>().1.draw_all ^= true;
-    // 自由相机来测试Ω
+    // 牌桌视角 F1 切到自由相机调试
     commands.spawn((
         Camera3d::default(),
         Transform::from_xyz(0., 0., 25.).looking_at(Vec3::ZERO, Vec3::Y),
-        CameraController::default(),
+        CameraRig::default(),
+        CameraController {
+            enabled: false,
+            ..default()
+        },
     ));
 
     commands.spawn((
@@ -50,39 +55,14 @@ fn setup(
         Transform::from_xyz(0.0, 0.0, 10.0),
     ));
 
-    // 场地相关的 素材
-    let case_images: CaseImages = CaseImages {
-        stone1: asset_server.load("stone_1.png"),
-        stone2: asset_server.load("stone_2.png"),
-        safe: asset_server.load("safe.png"),
-        lx: asset_server.load("lx.png"),
-        jq: asset_server.load("jq.png"),
-    };
-
-    render_case(&mut commands, &mut meshes, &mut materials, case_images);
-
-    // 设置两个用来触发的 平面 用来计算当前鼠标的位置
-    let card_plane =
-        Transform::from_xyz(0.0, 0.0, 18.0).with_rotation(Quat::from_axis_angle(Vec3::X, PI / 2.0));
-    let case_plane =
-        Transform::from_xyz(0.0, 0.0, 0.0).with_rotation(Quat::from_axis_angle(Vec3::X, PI / 2.0));
-
-    commands.spawn((CardPlane, card_plane));
-    commands.spawn((CasePlane, case_plane));
-
-    // 卡片放置器 放置在查看面上
+    // 场地和两个平面由 BoardPlugin 生成 卡片放置器 放置在查看面上
     let mut card_fn = gen_put_card::<CardPlane>(
         &mut commands,
         &mut materials,
         &mut meshes,
-        3. / 1.4,
-        3.,
-        0.05,
-        0.01,
-    );
-    let yellow = asset_server.load("NAAI-A-001.png");
-    card_fn(
-        yellow.clone(),
-        Transform::from_xyz(0., 0., card_plane.translation.z),
+        &mut cache,
+        cards_config.mesh_builder(),
     );
+    let yellow = asset_server.load(AssetManifest::card_image_path("NAAI-A-001"));
+    card_fn(yellow.clone(), board.hand_transform(0., 0.));
 }
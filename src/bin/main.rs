@@ -1,37 +1,29 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use card_test::cards::gen_put_card;
+use card_test::board::{BoardConfig, BoardPlugin};
+use card_test::cards::{gen_put_card, CardMeshCache, CardsConfig};
+use card_test::loading::{AssetManifest, LoadingPlugin, MatchState};
+use card_test::CardGamePlugins;
 use std::f32::consts::PI;
 
-#[derive(States, Hash, Clone, PartialEq, Eq, Debug, Default)]
-enum GameState {
-    #[default]
-    Loading,
-    Ready,
-}
-
-#[derive(Resource, Default)]
-struct MyAssets {
-    pub vertin: Handle<Image>,
-    pub yellow: Handle<Image>,
-}
-
 fn main() {
     App::new()
-        .add_plugins((DefaultPlugins, MeshPickingPlugin))
-        .init_resource::<MyAssets>()
+        .add_plugins((
+            DefaultPlugins,
+            // 这里只看单张卡片 不生成棋盘
+            CardGamePlugins
+                .set(BoardPlugin::new(
+                    BoardConfig::default().with_spawn_board(false),
+                ))
+                .set(LoadingPlugin::new(
+                    AssetManifest::default()
+                        .with_texture("default.png")
+                        .with_card("NAAI-A-001"),
+                )),
+        ))
         .add_plugins(WorldInspectorPlugin::new())
-        .add_systems(
-            Startup,
-            |asset_server: Res<AssetServer>, mut assets: ResMut<MyAssets>| {
-                let vertin = asset_server.load("default.png");
-                let yellow = asset_server.load("NAAI-A-001.png");
-                assets.vertin = vertin;
-                assets.yellow = yellow;
-            },
-        )
-        .add_systems(Update, setup.run_if(in_state(GameState::Loading)))
-        .init_state::<GameState>()
+        // 卡图加载完才生成卡片
+        .add_systems(OnEnter(MatchState::InMatch), setup)
         .run();
 }
 
@@ -42,21 +34,10 @@ fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
-    assets: Res<MyAssets>,
-    mut next_state: ResMut<NextState<GameState>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<CardMeshCache>,
+    cards_config: Res<CardsConfig>,
 ) {
-    // if !asset_server
-    //     .get_load_state(assets.vertin.id())
-    //     .is_some_and(|s| s.is_loaded())
-    //     || !asset_server
-    //         .get_load_state(assets.yellow.id())
-    //         .is_some_and(|s| s.is_loaded())
-    // {
-    //     return;
-    // }
-    next_state.set(GameState::Ready);
-
     //相机
     commands
         .spawn(Camera3d::default())
@@ -100,12 +81,10 @@ fn setup(
         &mut commands,
         &mut materials,
         &mut meshes,
-        3. / 1.4,
-        3.,
-        0.05,
-        0.01,
+        &mut cache,
+        cards_config.mesh_builder(),
     );
-    card_fn(assets.vertin.clone(), Transform::default());
+    card_fn(asset_server.load("default.png"), Transform::default());
     // card_fn(
     //     assets.vertin.clone(),
     //     Transform::from_xyz(-0.5, -0.5, 0.0001),
@@ -2,51 +2,66 @@ use bevy::app::App;
 use bevy::asset::{Assets, Handle};
 use bevy::DefaultPlugins;
 
-use bevy::math::{Quat, Vec2};
 use bevy::pbr::StandardMaterial;
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_tween::prelude::*;
-use bevy_tween::DefaultTweenPlugins;
-use card_test::camera_controller::{CameraController, CameraControllerPlugin};
-use card_test::cards::{gen_put_card, Card, Dragging, Setted};
-use card_test::cases::{render_case, CaseImages, CasePlane};
-use card_test::{CommonPlugin, MainCamera};
-use std::f32::consts::PI;
+use card_test::animations::{CardAnimationExt, FaceUp, Tapped};
+use card_test::camera_controller::CameraController;
+use card_test::camera_rig::CameraRig;
+use card_test::board::{BoardConfig, CardPlane};
+use card_test::card_face::{Deck, InDeck};
+use card_test::cards::{
+    gen_put_card, Card, CardLocation, CardMeshCache, CardStats, CardText, CardsConfig, Dragging,
+    Setted,
+};
+use card_test::game::{Owner, PlayerBundle, Resources, ZoneCounts};
+use card_test::loading::{AssetManifest, LoadingPlugin, MatchState};
+use card_test::locale::{CardId, Localization};
+use card_test::play::PendingPlay;
+use card_test::presentation::PresentationQueue;
+use card_test::prompt::{no_prompt_active, ChoicePrompt, PromptOption, PromptResult};
+use card_test::{CardGamePlugins, MainCamera};
 
 fn main() {
     App::new()
         .add_plugins((
             DefaultPlugins,
-            MeshPickingPlugin,
-            // CameraControllerPlugin,
-            // 动画相关
-            DefaultTweenPlugins,
-            CommonPlugin,
+            CardGamePlugins.set(LoadingPlugin::new(
+                AssetManifest::default()
+                    .with_card("NAAI-A-001")
+                    .with_texture("vertin.jpg"),
+            )),
         ))
         .add_plugins(WorldInspectorPlugin::new())
-        .add_systems(Startup, setup)
+        .add_systems(OnEnter(MatchState::InMatch), setup)
         .add_systems(Update, change_trans)
-        .add_systems(Update, card_test::cards::clear_on_finish_system)
+        .add_systems(Update, play_presets)
+        .add_systems(Update, skip_presentation)
+        .add_systems(Update, (prompt_demo, log_prompt_result, switch_language))
         .run();
 }
 
-#[derive(Component)]
-pub struct CardPlane;
-
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<CardMeshCache>,
     asset_server: Res<AssetServer>,
+    board: Res<BoardConfig>,
+    cards_config: Res<CardsConfig>,
 ) {
     // config_store.config_mut::<AabbGizmoConfigGroup>().1.draw_all ^= true;
-    // 自由相机来测试Ω
+    // 牌桌视角 F1 切到自由相机调试
     commands.spawn((
         MainCamera,
         Camera3d::default(),
         Transform::from_xyz(0., 0., 25.).looking_at(Vec3::ZERO, Vec3::Y),
-        CameraController::default(),
+        CameraRig::default(),
+        CameraController {
+            enabled: false,
+            ..default()
+        },
     ));
 
     commands.spawn((
@@ -57,53 +72,68 @@ fn setup(
         Transform::from_xyz(0.0, 0.0, 10.0),
     ));
 
-    // 场地相关的 素材
-    let case_images: CaseImages = CaseImages {
-        stone1: asset_server.load("stone_1.png"),
-        stone2: asset_server.load("stone_2.png"),
-        safe: asset_server.load("safe.png"),
-        lx: asset_server.load("lx.png"),
-        jq: asset_server.load("jq.png"),
-    };
-
-    render_case(&mut commands, &mut meshes, &mut materials, case_images);
-
-    // 设置两个用来触发的 平面 用来计算当前鼠标的位置
-    let card_plane =
-        Transform::from_xyz(0.0, 0.0, 18.0).with_rotation(Quat::from_axis_angle(Vec3::X, PI / 2.0));
-    let case_plane =
-        Transform::from_xyz(0.0, 0.0, 0.0).with_rotation(Quat::from_axis_angle(Vec3::X, PI / 2.0));
-
-    commands.spawn((CardPlane, card_plane));
-    commands.spawn((CasePlane, case_plane));
-
-    // 卡片放置器 放置在查看面上
+    // 场地和两个平面由 BoardPlugin 生成 卡片放置器 放置在查看面上
     let mut card_fn = gen_put_card::<CardPlane>(
         &mut commands,
         &mut materials,
         &mut meshes,
-        3. / 1.4,
-        3.,
-        0.05,
-        0.01,
-    );
-    let yellow = asset_server.load("NAAI-A-001.png");
-    card_fn(
-        yellow.clone(),
-        Transform::from_xyz(0., -4., card_plane.translation.z),
+        &mut cache,
+        cards_config.mesh_builder(),
     );
+    let yellow = asset_server.load(AssetManifest::card_image_path("NAAI-A-001"));
+    let card = card_fn(yellow.clone(), board.hand_transform(0., -4.));
+    drop(card_fn);
+
+    // 对局双方
+    let player = commands
+        .spawn(PlayerBundle {
+            resources: Resources { lx: 3, jq: 1 },
+            counts: ZoneCounts {
+                deck: 30,
+                ..default()
+            },
+            ..PlayerBundle::new("player.local", 0, 20)
+        })
+        .id();
+    commands.spawn(PlayerBundle {
+        counts: ZoneCounts {
+            deck: 30,
+            hand: 5,
+            ..default()
+        },
+        ..PlayerBundle::new("player.opponent", 1, 20)
+    });
+
+    // 本地玩家的牌组用单独的卡背
+    let deck = commands
+        .spawn(Deck {
+            back: asset_server.load("vertin.jpg"),
+        })
+        .id();
+
+    commands.entity(card).insert((
+        CardId("NAAI-A-001".to_string()),
+        CardStats {
+            cost: 2,
+            attack: Some(3),
+            health: Some(2),
+        },
+        Owner(player),
+        InDeck(deck),
+    ));
 }
 
 // 测试移动效果
 pub fn change_trans(
     mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    board: Res<BoardConfig>,
     mut card: Query<(Entity, &mut Transform, &mut Card), With<Card>>,
 ) {
     if keyboard_input.just_pressed(KeyCode::KeyA) {
         card.iter_mut()
             .for_each(|(entity, mut transform, mut card)| {
-                let at = Transform::from_xyz(0., -4., 18.0);
+                let at = board.hand_transform(0., -4.);
                 info!("{:?}", at);
                 info!("{:?}", card);
                 commands
@@ -111,10 +141,97 @@ pub fn change_trans(
                     .remove::<Setted>()
                     .remove::<Dragging>()
                     .remove::<PickingBehavior>()
-                    .insert(Card { trans: at.clone() });
+                    .remove::<PendingPlay>()
+                    .insert((Card { trans: at.clone() }, CardLocation::Hand));
                 transform.translation.x = at.translation.x;
                 transform.translation.y = at.translation.y;
                 transform.translation.z = at.translation.z;
             })
     }
 }
+
+// 测试卡片的预设动画
+pub fn play_presets(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    card: Query<(Entity, &Transform, &FaceUp, Has<Tapped>), With<Card>>,
+) {
+    card.iter().for_each(|(entity, transform, face_up, tapped)| {
+        let mut entity_commands = commands.entity(entity);
+        if keyboard_input.just_pressed(KeyCode::KeyF) {
+            entity_commands.flip_card(transform, !face_up.0);
+        } else if keyboard_input.just_pressed(KeyCode::KeyT) {
+            if tapped {
+                entity_commands.untap_card(transform);
+            } else {
+                entity_commands.tap_card(transform);
+            }
+        } else if keyboard_input.just_pressed(KeyCode::KeyG) {
+            entity_commands.lift_and_slam_card(transform);
+        } else if keyboard_input.just_pressed(KeyCode::KeyH) {
+            entity_commands.shake_card(transform);
+        }
+    })
+}
+
+// 空格跳过正在排队的表现
+pub fn skip_presentation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut presentation: ResMut<PresentationQueue>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        presentation.skip();
+    }
+}
+
+// P 弹出模式选择 O 从场上选两张卡
+pub fn prompt_demo(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    prompts: Query<(), With<ChoicePrompt>>,
+    cards: Query<(Entity, Option<&CardText>), With<Card>>,
+) {
+    if !no_prompt_active(prompts) {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyP) {
+        commands.spawn(ChoicePrompt::choose_mode(
+            "prompt.demo.mode",
+            &[
+                "prompt.demo.mode.draw",
+                "prompt.demo.mode.heal",
+                "prompt.demo.mode.damage",
+            ],
+        ));
+    } else if keyboard_input.just_pressed(KeyCode::KeyO) {
+        let options: Vec<PromptOption> = cards
+            .iter()
+            .map(|(entity, text)| {
+                let label = text.map_or("prompt.card".to_string(), |text| text.name.clone());
+                PromptOption::entity(entity, label)
+            })
+            .collect();
+        let max = options.len().min(2);
+        commands.spawn(ChoicePrompt::pick_cards("prompt.demo.cards", options, 1, max));
+    }
+}
+
+pub fn log_prompt_result(mut results: EventReader<PromptResult>) {
+    for result in results.read() {
+        if result.cancelled {
+            info!("取消选择");
+        } else {
+            info!("选择了 {:?} {:?}", result.selected, result.entities);
+        }
+    }
+}
+
+// J 切换语言
+pub fn switch_language(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut localization: ResMut<Localization>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyJ) {
+        localization.cycle_language();
+    }
+}
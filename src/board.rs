@@ -0,0 +1,139 @@
+use crate::cases::{render_case, CaseImages, CasePlane};
+use crate::loading::MatchState;
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+// 手牌所在的平面 拖拽时用它计算指针在手牌高度上的位置
+#[derive(Component, Debug, Default)]
+pub struct CardPlane;
+
+// 场地图片的路径 相对 assets 目录
+#[derive(Debug, Clone)]
+pub struct CaseImagePaths {
+    pub stone1: String,
+    pub stone2: String,
+    pub safe: String,
+    pub lx: String,
+    pub jq: String,
+}
+
+impl Default for CaseImagePaths {
+    fn default() -> Self {
+        Self {
+            stone1: "stone_1.png".to_string(),
+            stone2: "stone_2.png".to_string(),
+            safe: "safe.png".to_string(),
+            lx: "lx.png".to_string(),
+            jq: "jq.png".to_string(),
+        }
+    }
+}
+
+impl CaseImagePaths {
+    pub fn load(&self, asset_server: &AssetServer) -> CaseImages {
+        CaseImages {
+            stone1: asset_server.load(&self.stone1),
+            stone2: asset_server.load(&self.stone2),
+            safe: asset_server.load(&self.safe),
+            lx: asset_server.load(&self.lx),
+            jq: asset_server.load(&self.jq),
+        }
+    }
+
+    pub fn paths(&self) -> [&str; 5] {
+        [&self.stone1, &self.stone2, &self.safe, &self.lx, &self.jq]
+    }
+}
+
+// 棋盘的配置 两个平面的深度和场地素材
+#[derive(Resource, Debug, Clone)]
+pub struct BoardConfig {
+    // 手牌平面离棋盘的高度 相机在 25
+    pub hand_plane_z: f32,
+    pub board_plane_z: f32,
+    pub images: CaseImagePaths,
+    // 关掉时只登记配置 棋盘由程序自己生成
+    pub spawn_board: bool,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self {
+            hand_plane_z: 18.0,
+            board_plane_z: 0.0,
+            images: CaseImagePaths::default(),
+            spawn_board: true,
+        }
+    }
+}
+
+impl BoardConfig {
+    pub fn with_hand_plane_z(mut self, z: f32) -> Self {
+        self.hand_plane_z = z;
+        self
+    }
+
+    pub fn with_images(mut self, images: CaseImagePaths) -> Self {
+        self.images = images;
+        self
+    }
+
+    pub fn with_spawn_board(mut self, spawn_board: bool) -> Self {
+        self.spawn_board = spawn_board;
+        self
+    }
+
+    // 手牌平面上的一个位置
+    pub fn hand_transform(&self, x: f32, y: f32) -> Transform {
+        Transform::from_xyz(x, y, self.hand_plane_z)
+    }
+
+    fn plane(z: f32) -> Transform {
+        Transform::from_xyz(0.0, 0.0, z).with_rotation(Quat::from_axis_angle(Vec3::X, PI / 2.0))
+    }
+}
+
+// 资源加载完进入对局时生成场地和两个用来计算鼠标位置的平面
+pub fn spawn_board(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    config: Res<BoardConfig>,
+) {
+    if !config.spawn_board {
+        return;
+    }
+    let case_images = config.images.load(&asset_server);
+    render_case(&mut commands, &mut meshes, &mut materials, case_images);
+
+    commands.spawn((CardPlane, BoardConfig::plane(config.hand_plane_z)));
+    commands.spawn((CasePlane, BoardConfig::plane(config.board_plane_z)));
+}
+
+// 棋盘在进入 MatchState::InMatch 时生成
+// 没有 LoadingPlugin 时直接从 InMatch 开始
+#[derive(Default)]
+pub struct BoardPlugin {
+    pub config: BoardConfig,
+}
+
+impl BoardPlugin {
+    pub fn new(config: BoardConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Plugin for BoardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .add_systems(OnEnter(MatchState::InMatch), spawn_board);
+    }
+
+    // 等所有插件都加完再看 LoadingPlugin 可能排在后面
+    fn finish(&self, app: &mut App) {
+        if !app.world().contains_resource::<State<MatchState>>() {
+            app.insert_state(MatchState::InMatch);
+        }
+    }
+}
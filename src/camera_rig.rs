@@ -0,0 +1,374 @@
+use crate::actions::{ActionState, InputAction, InputActionsPlugin};
+use crate::camera_controller::{CameraController, CameraControllerPlugin};
+use crate::cases::CaseZone;
+use crate::dialog::{no_dialog_open, DialogStack};
+use crate::focus::FocusCursor;
+use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::picking::focus::HoverMap;
+use bevy::prelude::*;
+use bevy::window::CursorGrabMode;
+use bevy_tween::interpolation::EaseKind;
+
+// 牌桌的几个固定视角
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraView {
+    // 正上方俯视整个棋盘
+    TopDown,
+    // 从自己这一侧斜着看
+    Player,
+    // 看对手的半场
+    Opponent,
+    // 拉近看某一个场地
+    Zone(Entity),
+}
+
+// 镜头看向棋盘上的一点 距离和俯仰角决定镜头的位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RigPose {
+    pub focus: Vec3,
+    pub distance: f32,
+    // 0 是正上方 正数往自己这一侧倾斜
+    pub pitch: f32,
+}
+
+impl RigPose {
+    pub fn new(focus: Vec3, distance: f32, pitch: f32) -> Self {
+        Self {
+            focus,
+            distance,
+            pitch,
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        let rotation = Quat::from_rotation_x(self.pitch);
+        Transform::from_translation(self.focus + rotation * Vec3::Z * self.distance)
+            .with_rotation(rotation)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RigTransition {
+    from: Transform,
+    elapsed: f32,
+    duration: f32,
+}
+
+// 临时看向某处 停留一会儿后回到原来的视角
+#[derive(Debug, Clone, Copy)]
+struct RigHold {
+    view: CameraView,
+    pose: RigPose,
+    remaining: f32,
+}
+
+// 挂在主相机上 平时由它决定镜头位置 调试时可以切到自由相机
+#[derive(Component, Debug, Clone)]
+pub struct CameraRig {
+    pub view: CameraView,
+    pub pose: RigPose,
+    // 镜头焦点能移动的范围
+    pub bounds: Rect,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    // 每秒平移的距离 按镜头距离缩放
+    pub pan_speed: f32,
+    // 每格滚轮拉近的比例
+    pub zoom_speed: f32,
+    pub transition_secs: f32,
+    pub ease: EaseKind,
+    transition: Option<RigTransition>,
+    hold: Option<RigHold>,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            view: CameraView::TopDown,
+            pose: RigPose::new(Vec3::ZERO, 25.0, 0.0),
+            bounds: Rect::new(-12.0, -8.6, 12.0, 8.6),
+            min_distance: 10.0,
+            max_distance: 35.0,
+            pan_speed: 20.0,
+            zoom_speed: 0.1,
+            transition_secs: 0.8,
+            ease: EaseKind::CubicInOut,
+            transition: None,
+            hold: None,
+        }
+    }
+}
+
+impl CameraRig {
+    // 固定视角的姿态 拉近场地时传入场地的位置
+    pub fn preset(view: CameraView, zone: Option<Vec3>) -> Option<RigPose> {
+        Some(match view {
+            CameraView::TopDown => RigPose::new(Vec3::ZERO, 25.0, 0.0),
+            CameraView::Player => RigPose::new(Vec3::new(0.0, -3.0, 0.0), 24.0, 0.35),
+            CameraView::Opponent => RigPose::new(Vec3::new(0.0, 4.0, 0.0), 22.0, 0.2),
+            CameraView::Zone(_) => RigPose::new(zone?, 12.0, 0.15),
+        })
+    }
+
+    // 从当前位置平滑过渡到新的姿态
+    pub fn go_to(&mut self, view: CameraView, pose: RigPose, current: &Transform) {
+        self.view = view;
+        self.pose = pose;
+        self.hold = None;
+        self.clamp();
+        self.start_transition(*current);
+    }
+
+    // 镜头推向一个位置 停留 hold_secs 后自动回去 视角本身不变
+    pub fn focus_on(&mut self, pose: RigPose, hold_secs: f32, current: &Transform) {
+        // 连续的事件只记住最初的视角
+        let (view, previous) = self
+            .hold
+            .take()
+            .map_or((self.view, self.pose), |hold| (hold.view, hold.pose));
+        self.hold = Some(RigHold {
+            view,
+            pose: previous,
+            remaining: hold_secs,
+        });
+        self.pose = pose;
+        self.clamp();
+        self.start_transition(*current);
+    }
+
+    pub fn start_transition(&mut self, from: Transform) {
+        self.transition = Some(RigTransition {
+            from,
+            elapsed: 0.0,
+            duration: self.transition_secs,
+        });
+    }
+
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    // 玩家自己移动镜头后不再自动回去
+    pub fn pan(&mut self, delta: Vec2) {
+        self.hold = None;
+        self.pose.focus += delta.extend(0.0) * self.pose.distance / 25.0;
+        self.clamp();
+    }
+
+    // 正数拉近
+    pub fn zoom(&mut self, amount: f32) {
+        self.hold = None;
+        self.pose.distance *= 1.0 - amount * self.zoom_speed;
+        self.clamp();
+    }
+
+    fn clamp(&mut self) {
+        let focus = self
+            .pose
+            .focus
+            .truncate()
+            .clamp(self.bounds.min, self.bounds.max);
+        self.pose.focus = focus.extend(self.pose.focus.z);
+        self.pose.distance = self
+            .pose
+            .distance
+            .clamp(self.min_distance, self.max_distance);
+    }
+}
+
+// 切换视角的请求 按键 演出都从这里进入
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraViewRequested(pub CameraView);
+
+// 1 俯视 2 自己 3 对手 4 拉近鼠标下的场地
+pub fn camera_preset_keys(
+    actions: Res<ActionState>,
+    hover_map: Res<HoverMap>,
+    zones: Query<(), With<CaseZone>>,
+    mut requests: EventWriter<CameraViewRequested>,
+) {
+    if actions.just_pressed(InputAction::CameraPreset1) {
+        requests.send(CameraViewRequested(CameraView::TopDown));
+    }
+    if actions.just_pressed(InputAction::CameraPreset2) {
+        requests.send(CameraViewRequested(CameraView::Player));
+    }
+    if actions.just_pressed(InputAction::CameraPreset3) {
+        requests.send(CameraViewRequested(CameraView::Opponent));
+    }
+    if actions.just_pressed(InputAction::CameraPreset4) {
+        let zone = hover_map
+            .values()
+            .flat_map(|hits| hits.keys())
+            .find(|entity| zones.contains(**entity));
+        if let Some(zone) = zone {
+            requests.send(CameraViewRequested(CameraView::Zone(*zone)));
+        }
+    }
+}
+
+pub fn apply_camera_view_requests(
+    mut requests: EventReader<CameraViewRequested>,
+    zones: Query<&GlobalTransform, With<CaseZone>>,
+    mut rigs: Query<(&Transform, &mut CameraRig)>,
+) {
+    for CameraViewRequested(view) in requests.read() {
+        let zone = match view {
+            CameraView::Zone(zone) => zones.get(*zone).ok().map(|tr| tr.translation()),
+            _ => None,
+        };
+        let Some(pose) = CameraRig::preset(*view, zone) else {
+            continue;
+        };
+        for (transform, mut rig) in rigs.iter_mut() {
+            rig.go_to(*view, pose, transform);
+        }
+    }
+}
+
+fn freecam_enabled(controller: Option<&CameraController>) -> bool {
+    controller.is_some_and(|controller| controller.enabled)
+}
+
+// 导航的方向键或者右键拖动平移 滚轮缩放 鼠标在界面上时不缩放
+// 键盘导航时方向键留给界面 对话框打开时整个暂停
+#[allow(clippy::too_many_arguments)]
+pub fn camera_pan_zoom_system(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    focus: Option<Res<FocusCursor>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mouse_scroll: Res<AccumulatedMouseScroll>,
+    hover_map: Res<HoverMap>,
+    nodes: Query<(), With<Node>>,
+    mut rigs: Query<(&mut CameraRig, Option<&CameraController>)>,
+) {
+    let navigating = focus.is_some_and(|focus| focus.is_active());
+    let mut pan = Vec2::ZERO;
+    for (action, direction) in [
+        (InputAction::NavUp, Vec2::Y),
+        (InputAction::NavDown, Vec2::NEG_Y),
+        (InputAction::NavLeft, Vec2::NEG_X),
+        (InputAction::NavRight, Vec2::X),
+    ] {
+        if actions.pressed(action) && !navigating {
+            pan += direction;
+        }
+    }
+    let drag = if mouse_input.pressed(MouseButton::Right) {
+        mouse_motion.delta
+    } else {
+        Vec2::ZERO
+    };
+    let over_ui = hover_map
+        .values()
+        .flat_map(|hits| hits.keys())
+        .any(|entity| nodes.contains(*entity));
+    let zoom = match mouse_scroll.unit {
+        _ if over_ui => 0.0,
+        MouseScrollUnit::Line => mouse_scroll.delta.y,
+        MouseScrollUnit::Pixel => mouse_scroll.delta.y / 16.0,
+    };
+    if pan == Vec2::ZERO && drag == Vec2::ZERO && zoom == 0.0 {
+        return;
+    }
+    for (mut rig, controller) in rigs.iter_mut() {
+        if freecam_enabled(controller) {
+            continue;
+        }
+        let speed = rig.pan_speed;
+        // 拖动时棋盘跟着鼠标走 屏幕的 y 朝下
+        rig.pan(pan * speed * time.delta_secs() + Vec2::new(-drag.x, drag.y) * 0.02);
+        if zoom != 0.0 {
+            rig.zoom(zoom);
+        }
+    }
+}
+
+// 按姿态摆放镜头 过渡中按缓动插值 临时看向别处时到时间再回去
+pub fn camera_rig_system(
+    time: Res<Time>,
+    mut rigs: Query<(&mut Transform, &mut CameraRig, Option<&CameraController>)>,
+) {
+    for (mut transform, mut rig, controller) in rigs.iter_mut() {
+        let idle = rig.transition.is_none() && rig.hold.is_none();
+        if freecam_enabled(controller) || (!rig.is_changed() && idle) {
+            continue;
+        }
+        let target = rig.pose.transform();
+        let ease = rig.ease;
+        let Some(transition) = rig.transition.as_mut() else {
+            *transform = target;
+            let Some(hold) = rig.hold.as_mut() else {
+                continue;
+            };
+            hold.remaining -= time.delta_secs();
+            if hold.remaining <= 0.0 {
+                let hold = *hold;
+                rig.hold = None;
+                rig.view = hold.view;
+                rig.pose = hold.pose;
+                rig.start_transition(*transform);
+            }
+            continue;
+        };
+        transition.elapsed += time.delta_secs();
+        let t = (transition.elapsed / transition.duration.max(f32::EPSILON)).min(1.0);
+        let k = ease.sample(t);
+        transform.translation = transition.from.translation.lerp(target.translation, k);
+        transform.rotation = transition.from.rotation.slerp(target.rotation, k);
+        if t >= 1.0 {
+            rig.transition = None;
+        }
+    }
+}
+
+// F1 切换自由相机 只用来调试 切回来时平滑回到原来的视角
+pub fn toggle_freecam(
+    actions: Res<ActionState>,
+    mut cameras: Query<(&Transform, &mut CameraRig, &mut CameraController)>,
+    mut windows: Query<&mut Window>,
+) {
+    if !actions.just_pressed(InputAction::ToggleFreecam) {
+        return;
+    }
+    for (transform, mut rig, mut controller) in cameras.iter_mut() {
+        controller.enabled = !controller.enabled;
+        if controller.enabled {
+            // 从当前的朝向开始飞
+            controller.initialized = false;
+        } else {
+            rig.start_transition(*transform);
+            // 自由相机可能锁住了鼠标
+            for mut window in windows.iter_mut() {
+                window.cursor_options.grab_mode = CursorGrabMode::None;
+                window.cursor_options.visible = true;
+            }
+        }
+    }
+}
+
+pub struct CameraRigPlugin;
+
+impl Plugin for CameraRigPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<InputActionsPlugin>() {
+            app.add_plugins(InputActionsPlugin);
+        }
+        app.add_plugins(CameraControllerPlugin)
+            .init_resource::<DialogStack>()
+            .add_event::<CameraViewRequested>()
+            .add_systems(
+                Update,
+                (
+                    toggle_freecam,
+                    camera_preset_keys,
+                    apply_camera_view_requests,
+                    camera_pan_zoom_system.run_if(no_dialog_open),
+                    camera_rig_system,
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,462 @@
+use crate::cards::{front_material, CardInfo, CardMeshCache, CardStats, CardText, CardsConfig};
+use crate::locale::Localization;
+use crate::theme::{FontRole, TextRole, UiTheme};
+use bevy::asset::{LoadState, RenderAssetUsages, UntypedAssetId};
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::RenderLayers;
+
+// 卡面离屏相机用的层 不会拍到场景里的精灵
+pub const CARD_FACE_LAYER: usize = 7;
+
+// 素材都到齐后离屏相机再多画几帧 保证布局和文字都已经画进贴图
+const FACE_SETTLE_FRAMES: u8 = 3;
+
+// 卡面合成用到的素材和尺寸
+#[derive(Resource, Debug, Clone)]
+pub struct CardFaceStyle {
+    // 卡面贴图的像素尺寸 和卡片的宽高比一致
+    pub size: UVec2,
+    pub frame: Color,
+    pub art_border: Color,
+    pub cost_gem: Handle<Image>,
+    pub stat_left: Handle<Image>,
+    pub stat_right: Handle<Image>,
+    // 没有牌组的卡片用的卡背
+    pub back: Handle<Image>,
+}
+
+impl FromWorld for CardFaceStyle {
+    fn from_world(world: &mut World) -> Self {
+        // 没有用 CardsPlugin 时用默认的路径
+        let config = world
+            .get_resource::<CardsConfig>()
+            .cloned()
+            .unwrap_or_default();
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            size: UVec2::new(500, 700),
+            frame: Color::srgb(0.12, 0.12, 0.14),
+            art_border: Color::srgb(0.45, 0.42, 0.36),
+            cost_gem: asset_server.load(&config.cost_gem),
+            stat_left: asset_server.load(&config.stat_left),
+            stat_right: asset_server.load(&config.stat_right),
+            back: asset_server.load(&config.default_back),
+        }
+    }
+}
+
+// 牌组 同一个牌组的卡片共用卡背
+#[derive(Component, Debug, Clone)]
+pub struct Deck {
+    pub back: Handle<Image>,
+}
+
+// 卡片属于哪个牌组
+#[derive(Component, Debug, Clone, Copy)]
+pub struct InDeck(pub Entity);
+
+// 卡片背面的网格
+#[derive(Component, Debug, Default)]
+pub struct CardBackFace;
+
+// 卡片上记录离屏相机和合成出来的贴图
+#[derive(Component, Debug)]
+pub struct CardFace {
+    pub camera: Entity,
+    pub root: Option<Entity>,
+    pub image: Handle<Image>,
+}
+
+// 离屏相机和卡面界面记录所属的卡片 卡片没了一起清理
+#[derive(Component, Debug)]
+pub struct CardFaceOf(pub Entity);
+
+// 卡面需要重画 离屏相机开着 等卡图和字体到齐再画几帧后关掉
+#[derive(Component, Debug, Clone)]
+pub struct FaceRedraw {
+    pub art: Handle<Image>,
+    pub font: Handle<Font>,
+    pub frames: u8,
+}
+
+fn face_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+fn face_text(
+    parent: &mut ChildBuilder,
+    value: String,
+    font: &Handle<Font>,
+    size: f32,
+    color: Color,
+    node: Node,
+) {
+    parent.spawn((
+        Text::new(value),
+        TextFont {
+            font: font.clone(),
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+        node,
+    ));
+}
+
+// 数值放在一张底图上居中
+fn face_badge(
+    parent: &mut ChildBuilder,
+    image: &Handle<Image>,
+    value: String,
+    font: &Handle<Font>,
+    size: f32,
+    color: Color,
+    node: Node,
+) {
+    parent
+        .spawn((
+            ImageNode::new(image.clone()),
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..node
+            },
+        ))
+        .with_children(|badge| face_text(badge, value, font, size, color, Node::default()));
+}
+
+// 卡面的层次 边框底色 -> 卡图 -> 名字条 -> 费用 -> 规则文本 -> 攻击和生命
+#[allow(clippy::too_many_arguments)]
+fn spawn_face_ui(
+    commands: &mut Commands,
+    card: Entity,
+    camera: Entity,
+    art: &Handle<Image>,
+    text: &CardText,
+    stats: Option<&CardStats>,
+    style: &CardFaceStyle,
+    theme: &UiTheme,
+    font: &Handle<Font>,
+) -> Entity {
+    let light = theme.text_color(TextRole::Light);
+    let dark = theme.text_color(TextRole::Dark);
+    let heading = theme.font_size(FontRole::Title) * 1.2;
+    let body = theme.font_size(FontRole::Body);
+    commands
+        .spawn((
+            CardFaceOf(card),
+            TargetCamera(camera),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(16.0)),
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(style.frame),
+        ))
+        .with_children(|face| {
+            // 名字条和右上角的费用
+            face.spawn((
+                Node {
+                    height: Val::Px(64.0),
+                    padding: UiRect::horizontal(Val::Px(16.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                BackgroundColor(theme.palette.panel),
+                BorderRadius::all(Val::Px(8.0)),
+            ))
+            .with_children(|bar| {
+                face_text(
+                    bar,
+                    text.name.clone(),
+                    font,
+                    heading,
+                    light,
+                    Node::default(),
+                );
+                if let Some(stats) = stats {
+                    face_badge(
+                        bar,
+                        &style.cost_gem,
+                        stats.cost.to_string(),
+                        font,
+                        heading,
+                        dark,
+                        Node {
+                            width: Val::Px(52.0),
+                            height: Val::Px(52.0),
+                            ..default()
+                        },
+                    );
+                }
+            });
+            // 卡图
+            face.spawn((
+                ImageNode::new(art.clone()),
+                Node {
+                    height: Val::Percent(48.0),
+                    border: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                BorderColor(style.art_border),
+            ));
+            // 规则文本
+            face.spawn((
+                Node {
+                    flex_grow: 1.0,
+                    padding: UiRect::all(Val::Px(14.0)),
+                    ..default()
+                },
+                BackgroundColor(theme.palette.dialog),
+                BorderRadius::all(Val::Px(8.0)),
+            ))
+            .with_children(|rules| {
+                face_text(rules, text.rules.clone(), font, body, dark, Node::default());
+            });
+            // 没有攻击生命的卡片不显示底下一排
+            let Some(stats) = stats.filter(|s| s.attack.is_some() || s.health.is_some()) else {
+                return;
+            };
+            face.spawn(Node {
+                height: Val::Px(60.0),
+                justify_content: JustifyContent::SpaceBetween,
+                ..default()
+            })
+            .with_children(|row| {
+                let node = Node {
+                    width: Val::Px(146.0),
+                    height: Val::Px(60.0),
+                    ..default()
+                };
+                let value = |v: Option<i32>| v.map_or(String::new(), |v| v.to_string());
+                face_badge(
+                    row,
+                    &style.stat_left,
+                    value(stats.attack),
+                    font,
+                    heading,
+                    light,
+                    node.clone(),
+                );
+                face_badge(
+                    row,
+                    &style.stat_right,
+                    value(stats.health),
+                    font,
+                    heading,
+                    light,
+                    node,
+                );
+            });
+        })
+        .id()
+}
+
+// 有文本的卡片才合成卡面 只有卡图的卡片保持原样
+// 文本 数值 卡图 语言 主题任意一个变化都重新生成
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn compose_card_faces(
+    mut commands: Commands,
+    style: Res<CardFaceStyle>,
+    theme: Res<UiTheme>,
+    localization: Res<Localization>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cards: Query<(
+        Entity,
+        Ref<CardText>,
+        Option<Ref<CardStats>>,
+        &Children,
+        Option<&mut CardFace>,
+    )>,
+    infos: Query<Ref<CardInfo>>,
+    mut cameras: Query<&mut Camera>,
+) {
+    let refresh = style.is_changed() || theme.is_changed() || localization.is_changed();
+    for (card, text, stats, children, face) in cards.iter_mut() {
+        let Some((front, info)) = children
+            .iter()
+            .find_map(|child| infos.get(*child).ok().map(|info| (*child, info)))
+        else {
+            continue;
+        };
+        let stats_changed = stats.as_ref().is_some_and(|s| s.is_changed());
+        if face.is_some() && !refresh && !text.is_changed() && !stats_changed && !info.is_changed()
+        {
+            continue;
+        }
+        let (camera, image) = match face.as_deref() {
+            Some(face) => {
+                if let Ok(mut camera) = cameras.get_mut(face.camera) {
+                    camera.is_active = true;
+                }
+                (face.camera, face.image.clone())
+            }
+            None => {
+                let image = images.add(face_image(style.size));
+                let camera = commands
+                    .spawn((
+                        CardFaceOf(card),
+                        Camera2d,
+                        Camera {
+                            target: RenderTarget::Image(image.clone()),
+                            order: -1,
+                            clear_color: ClearColorConfig::Custom(Color::NONE),
+                            ..default()
+                        },
+                        RenderLayers::layer(CARD_FACE_LAYER),
+                    ))
+                    .id();
+                (camera, image)
+            }
+        };
+        let root = spawn_face_ui(
+            &mut commands,
+            card,
+            camera,
+            &info.image,
+            &text,
+            stats.as_deref(),
+            &style,
+            &theme,
+            &localization.font(),
+        );
+        commands.entity(camera).insert(FaceRedraw {
+            art: info.image.clone(),
+            font: localization.font(),
+            frames: FACE_SETTLE_FRAMES,
+        });
+        match face {
+            Some(mut face) => {
+                if let Some(old) = face.root.replace(root) {
+                    commands.entity(old).despawn_recursive();
+                }
+            }
+            // 合成的卡面每张卡都不同 不走共用的正面材质
+            None => {
+                commands
+                    .entity(front)
+                    .insert(MeshMaterial3d(materials.add(front_material(image.clone()))));
+                commands.entity(card).insert(CardFace {
+                    camera,
+                    root: Some(root),
+                    image,
+                });
+            }
+        }
+    }
+}
+
+// 卡面画好后关掉离屏相机 不然每张卡每帧都要重画一遍
+// 加载失败的素材不再等
+pub fn sleep_card_face_cameras(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    style: Res<CardFaceStyle>,
+    images: Res<Assets<Image>>,
+    fonts: Res<Assets<Font>>,
+    mut cameras: Query<(Entity, &mut Camera, &mut FaceRedraw)>,
+) {
+    let settled = |id: UntypedAssetId, present: bool| {
+        present || matches!(asset_server.load_state(id), LoadState::Failed(_))
+    };
+    let image_ready = |image: &Handle<Image>| settled(image.id().untyped(), images.contains(image));
+    let badges_ready = [&style.cost_gem, &style.stat_left, &style.stat_right]
+        .into_iter()
+        .all(image_ready);
+    for (entity, mut camera, mut redraw) in cameras.iter_mut() {
+        let font_ready = redraw.font == Handle::default()
+            || settled(redraw.font.id().untyped(), fonts.contains(&redraw.font));
+        if !badges_ready || !image_ready(&redraw.art) || !font_ready {
+            continue;
+        }
+        redraw.frames = redraw.frames.saturating_sub(1);
+        if redraw.frames == 0 {
+            camera.is_active = false;
+            commands.entity(entity).remove::<FaceRedraw>();
+        }
+    }
+}
+
+// 卡背跟着牌组走 没有牌组用默认卡背 同一个卡背共用材质
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn apply_card_backs(
+    mut commands: Commands,
+    style: Res<CardFaceStyle>,
+    mut cache: ResMut<CardMeshCache>,
+    decks: Query<&Deck>,
+    changed_decks: Query<(), Changed<Deck>>,
+    cards: Query<(Option<Ref<InDeck>>, &Children)>,
+    backs: Query<(
+        Entity,
+        &Parent,
+        Ref<CardBackFace>,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let refresh = style.is_changed() || !changed_decks.is_empty();
+    for (entity, parent, marker, material) in backs.iter() {
+        let Ok((deck, _)) = cards.get(parent.get()) else {
+            continue;
+        };
+        let deck_changed = deck.as_ref().is_some_and(|d| d.is_changed());
+        if !refresh && !marker.is_added() && !deck_changed {
+            continue;
+        }
+        let back = deck
+            .and_then(|deck| decks.get(deck.0).ok())
+            .map_or(style.back.clone(), |deck| deck.back.clone());
+        let back = cache.back_material(&mut materials, &back);
+        if material.0 != back {
+            commands.entity(entity).insert(MeshMaterial3d(back));
+        }
+    }
+}
+
+// 卡片移除后清理离屏相机和卡面界面
+pub fn cleanup_card_faces(
+    mut commands: Commands,
+    faces: Query<(Entity, &CardFaceOf)>,
+    cards: Query<(), With<CardFace>>,
+) {
+    for (entity, face_of) in faces.iter() {
+        if cards.get(face_of.0).is_err() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct CardFacePlugin;
+
+impl Plugin for CardFacePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CardFaceStyle>().add_systems(
+            Update,
+            (
+                (compose_card_faces, sleep_card_face_cameras).chain(),
+                apply_card_backs,
+                cleanup_card_faces,
+            ),
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use std::f32::consts::FRAC_PI_2;
+
+// 圆角卡片的网格 正面和背面各一块完整的圆角矩形 侧边和倒角合成一圈
+// 正面背面的 uv 铺满整张卡 侧边的 u 沿周长 v 从正面到背面
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CardMeshBuilder {
+    pub width: f32,
+    pub height: f32,
+    pub radius: f32,
+    pub thick: f32,
+    // 每个圆角分几段
+    pub corner_segments: u32,
+    // 正反面边缘往里收的距离 0 就是直角的侧边
+    pub bevel: f32,
+}
+
+// 一张卡片用到的三块网格
+#[derive(Debug, Clone)]
+pub struct CardMeshes {
+    pub front: Handle<Mesh>,
+    pub back: Handle<Mesh>,
+    pub edge: Handle<Mesh>,
+}
+
+// 轮廓上的点和朝外的方向
+type Outline = Vec<(Vec2, Vec2)>;
+
+impl CardMeshBuilder {
+    pub fn new(width: f32, height: f32, radius: f32, thick: f32) -> Self {
+        Self {
+            width,
+            height,
+            radius,
+            thick,
+            corner_segments: 8,
+            bevel: 0.0,
+        }
+    }
+
+    pub fn with_corner_segments(mut self, corner_segments: u32) -> Self {
+        self.corner_segments = corner_segments;
+        self
+    }
+
+    pub fn with_bevel(mut self, bevel: f32) -> Self {
+        self.bevel = bevel;
+        self
+    }
+
+    // f32 不能做 key 用它的位表示
+    pub fn key(&self) -> [u32; 6] {
+        [
+            self.width.to_bits(),
+            self.height.to_bits(),
+            self.radius.to_bits(),
+            self.thick.to_bits(),
+            self.corner_segments,
+            self.bevel.to_bits(),
+        ]
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius.clamp(0.0, self.width.min(self.height) / 2.0)
+    }
+
+    // 倒角不能超过圆角和一半厚度
+    fn bevel(&self) -> f32 {
+        self.bevel.clamp(0.0, self.radius().min(self.thick / 2.0))
+    }
+
+    // 逆时针的轮廓 从右上角开始 返回点和朝外的方向
+    fn outline(&self, inset: f32) -> Outline {
+        let radius = self.radius();
+        let segments = self.corner_segments.max(1);
+        let center = Vec2::new(self.width / 2.0 - radius, self.height / 2.0 - radius);
+        let mut points = vec![];
+        for (corner, sign) in [
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            for step in 0..=segments {
+                let angle = corner as f32 * FRAC_PI_2 + FRAC_PI_2 * step as f32 / segments as f32;
+                let normal = Vec2::from_angle(angle);
+                points.push((center * sign + normal * (radius - inset), normal));
+            }
+        }
+        points
+    }
+
+    fn uv(&self, point: Vec2) -> Vec2 {
+        Vec2::new(point.x / self.width + 0.5, 0.5 - point.y / self.height)
+    }
+
+    // 一面完整的圆角矩形 从中心扇形展开
+    fn face(&self, front: bool) -> Mesh {
+        let z = if front {
+            self.thick / 2.0
+        } else {
+            -self.thick / 2.0
+        };
+        let normal = if front { Vec3::Z } else { Vec3::NEG_Z };
+        let outline = self.outline(self.bevel());
+        let mut positions = vec![[0.0, 0.0, z]];
+        let mut uvs = vec![[0.5, 0.5]];
+        for (point, _) in &outline {
+            positions.push([point.x, point.y, z]);
+            let mut uv = self.uv(*point);
+            // 从背后看时左右是反的
+            if !front {
+                uv.x = 1.0 - uv.x;
+            }
+            uvs.push(uv.to_array());
+        }
+        let count = outline.len() as u32;
+        let mut indices = vec![];
+        for i in 0..count {
+            let (a, b) = (i + 1, (i + 1) % count + 1);
+            if front {
+                indices.extend([0, a, b]);
+            } else {
+                indices.extend([0, b, a]);
+            }
+        }
+        let normals = vec![normal.to_array(); positions.len()];
+        build_mesh(positions, normals, uvs, indices)
+    }
+
+    pub fn front(&self) -> Mesh {
+        self.face(true)
+    }
+
+    pub fn back(&self) -> Mesh {
+        self.face(false)
+    }
+
+    // 侧边一圈 有倒角时是四层 正面内圈 -> 外圈 -> 外圈 -> 背面内圈
+    pub fn edge(&self) -> Mesh {
+        let bevel = self.bevel();
+        let half = self.thick / 2.0;
+        let outer = self.outline(0.0);
+        let inner = self.outline(bevel);
+        let rings: Vec<(&Outline, f32, f32)> = if bevel > 0.0 {
+            vec![
+                (&inner, half, 1.0),
+                (&outer, half - bevel, 0.0),
+                (&outer, bevel - half, 0.0),
+                (&inner, -half, -1.0),
+            ]
+        } else {
+            vec![(&outer, half, 0.0), (&outer, -half, 0.0)]
+        };
+
+        // 周长方向的 u 首尾各多一个点 贴图可以接上
+        let mut lengths = vec![0.0];
+        for i in 1..=outer.len() {
+            let step = outer[i % outer.len()].0.distance(outer[i - 1].0);
+            lengths.push(lengths[i - 1] + step);
+        }
+        let perimeter = lengths.last().copied().unwrap_or(1.0).max(f32::EPSILON);
+
+        let mut positions = vec![];
+        let mut normals = vec![];
+        let mut uvs = vec![];
+        let columns = outer.len() + 1;
+        for (ring, (outline, z, tilt)) in rings.iter().enumerate() {
+            for (column, length) in lengths.iter().enumerate() {
+                let (point, normal) = outline[column % outline.len()];
+                positions.push([point.x, point.y, *z]);
+                normals.push(normal.extend(*tilt).normalize().to_array());
+                uvs.push([length / perimeter, ring as f32 / (rings.len() - 1) as f32]);
+            }
+        }
+        let mut indices = vec![];
+        for ring in 0..rings.len() - 1 {
+            for column in 0..columns - 1 {
+                let a = (ring * columns + column) as u32;
+                let b = a + 1;
+                let d = a + columns as u32;
+                let c = d + 1;
+                indices.extend([a, d, b, b, d, c]);
+            }
+        }
+        build_mesh(positions, normals, uvs, indices)
+    }
+
+    pub fn build(&self, meshes: &mut Assets<Mesh>) -> CardMeshes {
+        CardMeshes {
+            front: meshes.add(self.front()),
+            back: meshes.add(self.back()),
+            edge: meshes.add(self.edge()),
+        }
+    }
+}
+
+fn build_mesh(
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+) -> Mesh {
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
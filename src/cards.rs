@@ -1,15 +1,27 @@
+use crate::animations::FaceUp;
+use crate::card_face::{CardBackFace, CardFacePlugin};
+use crate::card_mesh::{CardMeshBuilder, CardMeshes};
 use crate::cases::CaseZone;
-use crate::spawn_ui_popup;
+use crate::drag::{move_on_drag, DragPlugin, DragRelease};
+use crate::focus::FocusCursor;
+use crate::inspect::{hover_preview_system, inspect_action_system, HoverInspect};
+use crate::play::{zone_occupied, GroupPlayRequested, PendingPlay, PlayRequested};
+use crate::presentation::PresentationQueue;
+use crate::prompt::ChoicePrompt;
+use crate::selection::{select_on_click, zone_at, DragFollower, Selected, SelectionPlugin};
+use crate::settings::{AnimationCategory, AnimationCategoryExt, AnimationSettings};
+use crate::touch::{TouchGestures, TouchPlugin};
 use bevy::ecs::observer::TriggerTargets;
+use bevy::picking::pointer::PointerId;
 use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::utils::HashMap;
 use bevy_inspector_egui::InspectorOptions;
-use bevy_tween::combinator::{
-    event, event_for, parallel, sequence, tween, TransformTargetStateExt,
-};
+use bevy_tween::combinator::{event_for, TransformTargetStateExt};
 use bevy_tween::interpolation::EaseKind;
 use bevy_tween::prelude::{AnimationBuilderExt, IntoTarget};
-use bevy_tween::tween::AnimationTarget;
-use std::f32::consts::PI;
+use bevy_tween::bevy_time_runner::{TimeRunner, TimeSpan, TimeSpanProgress};
+use bevy_tween::tween::{AnimationTarget, TweenInterpolationValue};
 use std::time::Duration;
 
 #[derive(Default, Component, Clone, Debug)]
@@ -18,81 +30,163 @@ pub struct Card {
 }
 
 #[derive(Component, Debug)]
-pub struct CardInfo {}
+pub struct CardInfo {
+    pub image: Handle<Image>,
+}
+
+// 卡片的名字和规则文本 悬停大图里显示
+#[derive(Component, Debug, Clone, Default)]
+pub struct CardText {
+    pub name: String,
+    pub rules: String,
+}
+
+// 卡片定义里的费用和攻击生命 合成卡面时显示
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct CardStats {
+    pub cost: u32,
+    pub attack: Option<i32>,
+    pub health: Option<i32>,
+}
 // 生成闭包的模板
 
 #[derive(Component, Debug)]
 pub struct Setted;
 
+// 卡片所在的区域
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CardLocation {
+    #[default]
+    Hand,
+    Zone(Entity),
+}
+
+// 卡片网格按形状缓存 边框和卡背的材质所有卡片共用
+// 生成一整副牌时网格只创建一次
+#[derive(Resource, Default)]
+pub struct CardMeshCache {
+    meshes: HashMap<[u32; 6], CardMeshes>,
+    frame: Option<Handle<StandardMaterial>>,
+    fronts: HashMap<AssetId<Image>, Handle<StandardMaterial>>,
+    backs: HashMap<AssetId<Image>, Handle<StandardMaterial>>,
+}
+
+impl CardMeshCache {
+    pub fn meshes(&mut self, meshes: &mut Assets<Mesh>, shape: &CardMeshBuilder) -> CardMeshes {
+        self.meshes
+            .entry(shape.key())
+            .or_insert_with(|| shape.build(meshes))
+            .clone()
+    }
+
+    pub fn frame_material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Handle<StandardMaterial> {
+        self.frame
+            .get_or_insert_with(|| materials.add(Color::BLACK))
+            .clone()
+    }
+
+    // 同一张卡图的正面共用一个材质
+    pub fn front_material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        image: &Handle<Image>,
+    ) -> Handle<StandardMaterial> {
+        self.fronts
+            .entry(image.id())
+            .or_insert_with(|| materials.add(front_material(image.clone())))
+            .clone()
+    }
+
+    // 同一个卡背共用一个材质
+    pub fn back_material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        image: &Handle<Image>,
+    ) -> Handle<StandardMaterial> {
+        self.backs
+            .entry(image.id())
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: Color::WHITE,
+                    base_color_texture: Some(image.clone()),
+                    alpha_mode: AlphaMode::Blend,
+                    ..Default::default()
+                })
+            })
+            .clone()
+    }
+}
+
+// 卡片正面不受光照影响
+pub fn front_material(image: Handle<Image>) -> StandardMaterial {
+    StandardMaterial {
+        base_color: Color::WHITE,
+        base_color_texture: Some(image),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..Default::default()
+    }
+}
+
+// 卡片本体是侧边 正面和背面两个子实体覆盖整张卡 拾取落在哪里都一样
 pub fn gen_put_card<C>(
     commands: &mut Commands,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     meshes: &mut ResMut<Assets<Mesh>>,
-    width: f32,
-    height: f32,
-    radius: f32,
-    thick: f32,
+    cache: &mut CardMeshCache,
+    shape: CardMeshBuilder,
 ) -> impl FnMut(Handle<Image>, Transform) -> Entity
 where
     C: Component,
 {
     move |images: Handle<Image>, transform: Transform| {
-        let mesh_list = gen_card_mesh_list(meshes, width, height, radius, thick);
+        let card_meshes = cache.meshes(meshes, &shape);
+        let frame = cache.frame_material(materials);
+        let front = cache.front_material(materials, &images);
 
         commands
             .spawn((
                 Card {
                     trans: transform.clone(),
                 },
+                FaceUp::default(),
+                CardLocation::Hand,
                 Visibility::Inherited,
                 transform,
                 AnimationTarget,
+                // 黑色的侧边 很薄 不参与拾取
+                Mesh3d(card_meshes.edge),
+                MeshMaterial3d(frame.clone()),
+                PickingBehavior::IGNORE,
             ))
             .with_children(|parent| {
-                // 加载黑色边框
-                for (mesh_handle, trans) in mesh_list.0 {
-                    parent.spawn((
-                        Mesh3d(mesh_handle.clone()),
-                        trans.clone(),
-                        MeshMaterial3d(materials.add(Color::BLACK)),
-                    ));
-                }
-                // 加载内容
-                for (mesh_handle, trans) in mesh_list.1 {
-                    parent.spawn((
-                        CardInfo {
-                            // todo 这里是卡片的信息内容
-                        },
-                        Mesh3d(mesh_handle.clone()),
-                        trans.clone(),
-                        MeshMaterial3d(materials.add(StandardMaterial {
-                            base_color: Color::WHITE,
-                            base_color_texture: Some(images.clone()),
-                            alpha_mode: AlphaMode::Blend,
-                            unlit: true,
-                            ..Default::default()
-                        })),
-                    ));
-                }
-                // 背面
-                for (mesh_handle, trans) in mesh_list.2 {
-                    parent.spawn((
-                        Mesh3d(mesh_handle.clone()),
-                        trans.clone(),
-                        MeshMaterial3d(materials.add(StandardMaterial {
-                            base_color: Color::WHITE,
-                            base_color_texture: Some(images.clone()),
-                            alpha_mode: AlphaMode::Blend,
-                            ..Default::default()
-                        })),
-                    ));
-                }
+                // 正面
+                parent.spawn((
+                    CardInfo {
+                        // todo 这里是卡片的信息内容
+                        image: images.clone(),
+                    },
+                    Mesh3d(card_meshes.front),
+                    MeshMaterial3d(front),
+                ));
+                // 背面 先和边框一样是黑色 卡背由牌组决定
+                parent.spawn((
+                    CardBackFace,
+                    Mesh3d(card_meshes.back),
+                    MeshMaterial3d(frame),
+                ));
             })
             .observe(move_on_drag::<C>())
             .observe(drag_start)
             .observe(drag_end)
             .observe(over_card)
             .observe(out_card)
+            .observe(press_card)
+            .observe(tap_card)
+            .observe(select_on_click)
             .id()
     }
 }
@@ -100,181 +194,382 @@ where
 // 处理拖拽到的代码
 pub fn deal_on_drop(
     drag_drop: Trigger<Pointer<DragDrop>>,
-    mut query: Query<&mut CaseZone>,
-    mut card_info_query: Query<&mut CardInfo>,
+    query: Query<(), With<CaseZone>>,
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut card_q: Query<&mut Card, Without<Setted>>,
-    mut p_q: Query<&Parent, With<CardInfo>>,
+    card_q: Query<(), (With<Card>, Without<Setted>)>,
+    p_q: Query<&Parent, With<CardInfo>>,
+    presentation: Res<PresentationQueue>,
 ) {
-    // 场地的值？ TODO 这处理
-    // info!("{:?}", drag_drop);
-
-    let case_zone = query.get_mut(drag_drop.target).unwrap();
-    let end = case_zone.clone().transform.translation;
-    // info!("{:?}", case_zone);
-    if let Ok(y) = card_info_query.get(drag_drop.dropped) {
-        // todo
+    // 表现还在播放时不接受新的出牌
+    if presentation.is_blocking() {
+        return;
+    }
+    if query.get(drag_drop.target).is_err() {
+        return;
     }
-    //todo 处理内部的场地和卡片的关系
-    // info!("{:?}", y);
     if let Ok(parent) = p_q.get(drag_drop.dropped) {
-        if let Ok(mut card) = card_q.get_mut(parent.get()) {
-            let p_clone = parent.get().clone();
-            // 设置卡片被放置了
-            let mut card_clone = card.clone();
-            let card_end = Card {
-                trans: Transform::from_translation(end.clone()),
-            };
-            spawn_ui_popup(
-                &mut commands,
-                &asset_server,
-                "是否登场?",
-                move |cmd, ch_q| {
-                    let target = AnimationTarget.into_target();
-                    let mut start = target.transform_state(card_clone.clone().trans);
-                    // // todo 这里应该一系列动画
-                    // card_clone.trans.translation = end.clone();
-                    let mut mid = Vec3::ZERO;
-                    mid.z = card_clone.trans.translation.z;
-
-                    let mut mid2 = Vec3::ZERO;
-                    mid2.z = card_clone.trans.translation.z + 3.0;
-                    info!("{:?}", card_clone.clone().trans.translation);
-                    info!("{:?}", mid);
-                    info!("{:?}", end);
-                    let mut mid_state = target.transform_state(Transform::from_translation(mid));
-                    let mut mid_state2 = target.transform_state(Transform::from_translation(mid));
-                    info!("add tween");
-                    cmd.entity(p_clone)
-                        .animation()
-                        .clear_on_finish()
-                        .insert(sequence((
-                            tween(
-                                Duration::from_secs_f32(1.0),
-                                EaseKind::ExponentialOut,
-                                start.translation_to(mid),
-                            ),
-                            tween(
-                                Duration::from_secs_f32(1.0),
-                                EaseKind::ExponentialOut,
-                                mid_state.translation_to(mid2),
-                            ),
-                            tween(
-                                Duration::from_secs_f32(0.6),
-                                EaseKind::ExponentialOut,
-                                mid_state2.translation_to(end),
-                            ),
-                            parallel((event("boom"), event("shark"))),
-                        )))
-                        .insert(card_end.clone())
-                        .insert(Setted);
-
-                    // 恢复自由身体
-                    if let Ok(children) = ch_q.get(p_clone) {
-                        for child in children.iter() {
-                            cmd.entity(child.clone()).remove::<PickingBehavior>();
-                        }
-                    }
-                    info!("确认");
-                },
-                move |cmd| {
-                    info!("取消");
-                },
-            );
+        if card_q.get(parent.get()).is_ok() {
+            commands.trigger(PlayRequested {
+                card: parent.get(),
+                zone: drag_drop.target,
+            });
         }
     }
 }
 
-#[derive(Component, Debug)]
-pub struct Dragging;
+// 触屏点一下场地 把选中的手卡打到这里 和拖拽过来一样
+pub fn tap_zone(
+    click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    zones: Query<(), With<CaseZone>>,
+    mut cursor: ResMut<FocusCursor>,
+    presentation: Res<PresentationQueue>,
+) {
+    if !click.pointer_id.is_touch() || presentation.is_blocking() {
+        return;
+    }
+    if !zones.contains(click.entity()) {
+        return;
+    }
+    if let Some(card) = cursor.held.take() {
+        commands.trigger(PlayRequested {
+            card,
+            zone: click.entity(),
+        });
+    }
+}
+
+// 正在拖拽 记录拖拽的指针和它所在的相机
+// target 是指针对应的位置 卡片平滑地追过去 rotation 是开始拖拽时的朝向
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Dragging {
+    pub pointer: PointerId,
+    pub camera: Entity,
+    pub target: Vec3,
+    pub velocity: Vec3,
+    pub rotation: Quat,
+}
+
+// 悬停时手卡移动到的高度
+const HOVER_HAND_Y: f32 = -2.0;
+// 悬停时朝镜头抬起的距离
+const HOVER_LIFT: f32 = 2.0;
+const HOVER_SCALE: f32 = 1.4;
+// 减少动效时的放大倍数
+const HOVER_SCALE_REDUCED: f32 = 1.1;
+// 相邻手卡向两边让开的距离
+const HOVER_SPREAD: f32 = 0.8;
+
+// 回到/离开原位都同时处理位移和缩放 避免残留的缩放 tween 被重新播放
+fn tween_card_to(commands: &mut Commands, card: Entity, from: &Transform, to: Vec3, scale: f32) {
+    let target = AnimationTarget.into_target();
+    let mut start = target.transform_state(*from);
+    commands
+        .entity(card)
+        .animation()
+        .category(AnimationCategory::Hover)
+        .insert_tween_here(
+            Duration::from_secs_f32(1.1),
+            EaseKind::ExponentialOut,
+            (start.translation_to(to), start.scale_to(Vec3::splat(scale))),
+        );
+}
 
+// 停掉卡片上正在播放的悬停 tween 不然它会继续改位置和缩放 跟拖拽抢
+fn stop_card_tween(commands: &mut Commands, card: Entity, tr: &Transform, rest: &Transform) {
+    commands
+        .entity(card)
+        .remove::<(TimeRunner, TimeSpan, TimeSpanProgress, TweenInterpolationValue)>()
+        .insert(tr.with_scale(rest.scale));
+}
+
+fn same_hand_row(a: &Card, b: &Card) -> bool {
+    (a.trans.translation.y - b.trans.translation.y).abs() < 0.01
+        && (a.trans.translation.z - b.trans.translation.z).abs() < 0.01
+}
+
+// 拖拽中和松手回弹中的卡片不参与悬停
+type IdleCards<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, &'static Card, Has<Setted>),
+    (Without<Dragging>, Without<DragRelease>),
+>;
+
+// 让手卡全部回到自己的位置
+fn settle_hand(commands: &mut Commands, cards: &IdleCards, except: &[Entity]) {
+    for (entity, tr, card, setted) in cards.iter() {
+        if setted || except.contains(&entity) {
+            continue;
+        }
+        tween_card_to(commands, entity, tr, card.trans.translation, 1.0);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn over_card(
-    out: Trigger<Pointer<Over>>,
+    over: Trigger<Pointer<Over>>,
     mut commands: Commands,
     query: Query<&Parent>,
-    query_transform: Query<(&Transform, &Card), (Without<Dragging>, Without<Setted>)>,
+    cards: IdleCards,
+    dragging: Query<(), With<Dragging>>,
+    settings: Res<AnimationSettings>,
+    presentation: Res<PresentationQueue>,
+    mut inspect: ResMut<HoverInspect>,
 ) {
-    if let Ok(parent) = query.get(out.target) {
-        if let Ok((tr, card)) = query_transform.get(parent.get()) {
-            let target = AnimationTarget.into_target();
-            let mut start = target.transform_state(tr.clone());
-            let mut end = tr.clone().translation;
-            end.y = -2.0;
-            info!("over");
-            info!("{:?}", tr);
-            info!("{:?}", end.clone());
-            commands.entity(parent.get()).animation().insert_tween_here(
-                Duration::from_secs_f32(1.1),
-                EaseKind::ExponentialOut,
-                start.translation_to(end),
-            );
+    // 出牌表现播放中或者正在拖拽时不响应悬停
+    if presentation.is_blocking() || !dragging.is_empty() {
+        return;
+    }
+    let Ok(parent) = query.get(over.target) else {
+        return;
+    };
+    let Ok((entity, tr, card, setted)) = cards.get(parent.get()) else {
+        return;
+    };
+    let mut end = card.trans.translation;
+    if !setted {
+        end.y = HOVER_HAND_Y;
+    }
+    end.z += HOVER_LIFT;
+    let scale = if settings.large_scaling() {
+        HOVER_SCALE
+    } else {
+        HOVER_SCALE_REDUCED
+    };
+    info!("over");
+    tween_card_to(&mut commands, entity, tr, end, scale);
+
+    // 场上的卡片不需要让位
+    if !setted {
+        for (other, other_tr, other_card, other_setted) in cards.iter() {
+            if other == entity || other_setted || !same_hand_row(card, other_card) {
+                continue;
+            }
+            let side = (other_card.trans.translation.x - card.trans.translation.x).signum();
+            let spread = other_card.trans.translation + Vec3::X * side * HOVER_SPREAD;
+            tween_card_to(&mut commands, other, other_tr, spread, 1.0);
         }
     }
+    // 触屏按下时也会触发 大图交给长按
+    if !over.pointer_id.is_touch() {
+        inspect.begin(entity);
+    }
 }
 
 pub fn out_card(
     out: Trigger<Pointer<Out>>,
     mut commands: Commands,
     query: Query<&Parent>,
-    query_transform: Query<(&Transform, &Card), (Without<Dragging>, Without<Setted>)>,
+    cards: IdleCards,
+    presentation: Res<PresentationQueue>,
+    mut inspect: ResMut<HoverInspect>,
 ) {
-    if let Ok(parent) = query.get(out.target) {
-        if let Ok((tr, card)) = query_transform.get(parent.get()) {
-            let target = AnimationTarget.into_target();
-            let mut start = target.transform_state(tr.clone());
-            info!("back");
-            info!("{:?}", tr.clone());
-            info!("{:?}", card);
-            commands.entity(parent.get()).animation().insert_tween_here(
-                Duration::from_secs_f32(1.1),
-                EaseKind::ExponentialOut,
-                start.translation_to(card.trans.translation),
-            );
-        }
+    // 出牌表现播放中不响应悬停
+    if presentation.is_blocking() {
+        return;
+    }
+    let Ok(parent) = query.get(out.target) else {
+        return;
+    };
+    let Ok((entity, tr, card, setted)) = cards.get(parent.get()) else {
+        return;
+    };
+    info!("back");
+    tween_card_to(&mut commands, entity, tr, card.trans.translation, 1.0);
+    if !setted {
+        settle_hand(&mut commands, &cards, &[entity]);
     }
+    inspect.end(entity);
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn drag_start(
     drag_start: Trigger<Pointer<DragStart>>,
     mut commands: Commands,
     query: Query<(), With<CardInfo>>,
     query_parent: Query<&Parent>,
-    card_query: Query<&Card, Without<Setted>>,
+    cards: IdleCards,
+    selected: Query<Entity, (With<Selected>, Without<PendingPlay>)>,
+    presentation: Res<PresentationQueue>,
+    mut inspect: ResMut<HoverInspect>,
+    prompts: Query<(), With<ChoicePrompt>>,
 ) {
+    // 演出中或者正在选择时不能拖动
+    if presentation.is_blocking() || !prompts.is_empty() {
+        return;
+    }
     if query.get(drag_start.target).is_ok() {
         commands
             .entity(drag_start.target)
             .insert(PickingBehavior::IGNORE);
     }
     // 添加拖拽中的组件
-    if let Ok(parent) = query_parent.get(drag_start.target) {
-        if let Ok(_card) = card_query.get(parent.get()) {
-            commands.entity(parent.get()).insert(Dragging);
+    // 以卡片的原位为准 悬停时的抬起和放大不算
+    let Some((entity, tr, card)) = query_parent
+        .get(drag_start.target)
+        .ok()
+        .and_then(|parent| cards.get(parent.get()).ok())
+        .filter(|(_, _, _, setted)| !setted)
+        .map(|(entity, tr, card, _)| (entity, tr, card))
+    else {
+        return;
+    };
+    stop_card_tween(&mut commands, entity, tr, &card.trans);
+    let dragging = Dragging {
+        pointer: drag_start.pointer_id,
+        camera: drag_start.hit.camera,
+        target: card.trans.translation,
+        velocity: Vec3::ZERO,
+        rotation: card.trans.rotation,
+    };
+    commands.entity(entity).insert(dragging);
+    // 拖的是选中的卡片时 其他选中的手卡保持队形跟着走
+    let mut group = vec![entity];
+    if selected.contains(entity) {
+        for (other, other_tr, other_card, setted) in cards.iter_many(selected.iter()) {
+            if other == entity || setted {
+                continue;
+            }
+            stop_card_tween(&mut commands, other, other_tr, &other_card.trans);
+            commands.entity(other).insert((
+                Dragging {
+                    target: other_card.trans.translation,
+                    rotation: other_card.trans.rotation,
+                    ..dragging
+                },
+                DragFollower {
+                    leader: entity,
+                    offset: other_card.trans.translation - card.trans.translation,
+                },
+            ));
+            group.push(other);
         }
     }
+    // 拖拽时收起大图 其他手卡回到原位
+    inspect.clear();
+    settle_hand(&mut commands, &cards, &group);
 }
 
+// 触屏按下卡片 开始计算长按
+pub fn press_card(
+    press: Trigger<Pointer<Down>>,
+    query: Query<&Parent, With<CardInfo>>,
+    cards: Query<(), With<Card>>,
+    mut gestures: ResMut<TouchGestures>,
+) {
+    if !press.pointer_id.is_touch() {
+        return;
+    }
+    let Ok(parent) = query.get(press.target) else {
+        return;
+    };
+    if cards.contains(parent.get()) {
+        gestures.begin(parent.get(), press.pointer_id);
+    }
+}
+
+// 触屏点一下手卡 选中后再点场地出牌 再点一次取消 长按之后松手不算
+#[allow(clippy::type_complexity)]
+pub fn tap_card(
+    click: Trigger<Pointer<Click>>,
+    query: Query<&Parent, With<CardInfo>>,
+    cards: Query<(), (With<Card>, Without<Setted>, Without<PendingPlay>)>,
+    gestures: Res<TouchGestures>,
+    presentation: Res<PresentationQueue>,
+    prompts: Query<(), With<ChoicePrompt>>,
+    mut cursor: ResMut<FocusCursor>,
+) {
+    if !click.pointer_id.is_touch() || presentation.is_blocking() || !prompts.is_empty() {
+        return;
+    }
+    let Ok(parent) = query.get(click.target) else {
+        return;
+    };
+    let card = parent.get();
+    if !cards.contains(card) || gestures.was_long_press(card) {
+        return;
+    }
+    if cursor.held == Some(card) {
+        cursor.held = None;
+    } else {
+        cursor.held = Some(card);
+        cursor.target = Some(card);
+        // 选中的卡片用焦点框标出来
+        cursor.active = true;
+    }
+}
+
+#[allow(clippy::type_complexity)]
 pub fn drag_end(
     drag_start: Trigger<Pointer<DragEnd>>,
     mut commands: Commands,
     query: Query<&Parent>,
-    query_transform: Query<(&Transform, &Card), Without<Setted>>,
+    mut query_transform: Query<
+        (&mut Transform, &Card, &Dragging, Option<&PendingPlay>),
+        (Without<Setted>, Without<DragFollower>),
+    >,
+    followers: Query<(Entity, &Transform, &Card, &Dragging, &DragFollower)>,
+    zones: Query<(Entity, &CaseZone, &GlobalTransform, Option<&Aabb>)>,
+    locations: Query<(Entity, &CardLocation)>,
 ) {
     info!("Drag END {:?}", drag_start.target);
-    // 发送回到原来位置的命令
+    // 带着松手时的速度回到原来的位置
     if let Ok(parent) = query.get(drag_start.target) {
-        if let Ok((tr, card)) = query_transform.get(parent.get()) {
-            let target = AnimationTarget.into_target();
-            let mut start = target.transform_state(tr.clone());
-
-            commands.entity(parent.get()).animation().insert_tween_here(
-                Duration::from_secs_f32(1.1),
-                EaseKind::ExponentialOut,
-                start.translation_to(card.trans.translation),
-            );
+        let leader = parent.get();
+        let leader_zone = query_transform
+            .get(leader)
+            .ok()
+            .and_then(|(_, _, _, pending)| pending.map(|pending| pending.zone));
+        // 跟着走的卡片落在空的场地上就和领头的卡一起出牌 只确认一次
+        // 场地被占用 或者和领头的卡 前面的卡落在同一个场地时弹回去
+        let mut group: Vec<_> = followers
+            .iter()
+            .filter(|(_, _, _, _, follower)| follower.leader == leader)
+            .collect();
+        group.sort_by(|a, b| a.1.translation.x.total_cmp(&b.1.translation.x));
+        let mut claimed: Vec<Entity> = leader_zone.into_iter().collect();
+        let mut plays = vec![];
+        for (entity, tr, card, dragging, _) in group {
+            commands
+                .entity(entity)
+                .remove::<(Dragging, DragFollower)>();
+            let zone = zone_at(tr.translation, &zones)
+                .filter(|zone| !claimed.contains(zone))
+                .filter(|zone| !zone_occupied(&locations, entity, *zone));
+            match zone {
+                Some(zone) => {
+                    claimed.push(zone);
+                    plays.push(PlayRequested { card: entity, zone });
+                }
+                None => {
+                    commands.entity(entity).insert(DragRelease {
+                        target: card.trans.translation,
+                        velocity: dragging.velocity,
+                        rotation: dragging.rotation,
+                    });
+                }
+            }
+        }
+        if !plays.is_empty() {
+            commands.trigger(GroupPlayRequested {
+                plays,
+                with: leader_zone.map(|_| leader),
+            });
+        }
+        if let Ok((mut tr, card, dragging, pending)) = query_transform.get_mut(leader) {
             // 删除拖拽中的组件
-            commands.entity(parent.get()).remove::<Dragging>();
+            commands.entity(leader).remove::<Dragging>();
+            // 等待确认的卡片停在场地上 由确认结果决定去向 只把倾斜摆正
+            if pending.is_some() {
+                tr.rotation = dragging.rotation;
+                return;
+            }
+            commands.entity(leader).insert(DragRelease {
+                target: card.trans.translation,
+                velocity: dragging.velocity,
+                rotation: dragging.rotation,
+            });
         }
     }
     commands
@@ -282,174 +577,74 @@ pub fn drag_end(
         .remove::<PickingBehavior>();
 }
 
-// 在这里个方法里 还可以做其他的事情 比如通知全局现在要选择
-pub fn move_on_drag<C>() -> impl Fn(
-    Trigger<Pointer<Drag>>,
-    Query<&mut Transform, Without<Setted>>,
-    Single<(&Camera, &GlobalTransform)>,
-    Single<&Window>,
-    Single<&GlobalTransform, With<C>>,
-)
-where
-    C: Component,
-{
-    move |drag, mut transforms, camera_query, windows, ground| {
-        // 这个是需要修改的值
-        if let Ok(mut transform) = transforms.get_mut(drag.entity()) {
-            let (camera, camera_transform) = *camera_query;
-
-            let Some(cursor_position) = windows.cursor_position() else {
-                info!("a");
-                return;
-            };
-
-            // Calculate a ray pointing from the camera into the world based on the cursor's position.
-            let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) else {
-                info!("b");
-                return;
-            };
+// 卡片的尺寸和卡面素材的路径 所有卡片共用
+#[derive(Resource, Debug, Clone)]
+pub struct CardsConfig {
+    pub width: f32,
+    pub height: f32,
+    pub radius: f32,
+    pub thick: f32,
+    pub cost_gem: String,
+    pub stat_left: String,
+    pub stat_right: String,
+    // 没有牌组的卡片用的卡背
+    pub default_back: String,
+}
 
-            // Calculate if and where the ray is hitting the ground plane.
-            let Some(distance) =
-                ray.intersect_plane(ground.translation(), InfinitePlane3d::new(ground.up()))
-            else {
-                info!("c");
-                return;
-            };
-            let point = ray.get_point(distance);
-            // info!("{:?}", point);
-            transform.translation.x = point.x;
-            transform.translation.y = point.y;
+impl Default for CardsConfig {
+    fn default() -> Self {
+        Self {
+            width: 3. / 1.4,
+            height: 3.,
+            radius: 0.05,
+            thick: 0.01,
+            cost_gem: "circle.png".to_string(),
+            stat_left: "statBoxLeft.png".to_string(),
+            stat_right: "statBoxRight.png".to_string(),
+            default_back: "default.png".to_string(),
         }
     }
 }
 
-fn gen_card_mesh_list(
-    meshes: &mut ResMut<Assets<Mesh>>,
-    width: f32,
-    height: f32,
-    radius: f32,
-    thick: f32,
-) -> (
-    [(Handle<Mesh>, Transform); 8],
-    [(Handle<Mesh>, Transform); 1],
-    [(Handle<Mesh>, Transform); 1],
-) {
-    // 四个 扇形 四个长方形  一个中央的部分
-    let a: f32 = width - 2.0 * radius;
-    let b: f32 = height - 2.0 * radius;
-
-    // 四个角的坐标
-    let right_top = Transform::from_xyz(a / 2.0, b / 2.0, 0.0)
-        .with_rotation(Quat::from_axis_angle(Vec3::Z, -PI / 4.));
-    let right_bottom = Transform::from_xyz(a / 2.0, -b / 2.0, 0.0)
-        .with_rotation(Quat::from_axis_angle(Vec3::Z, -PI / 4. - PI / 2.));
-
-    let left_top = Transform::from_xyz(-a / 2.0, b / 2.0, 0.0)
-        .with_rotation(Quat::from_axis_angle(Vec3::Z, PI / 4.));
-
-    let left_bottom = Transform::from_xyz(-a / 2.0, -b / 2.0, 0.0)
-        .with_rotation(Quat::from_axis_angle(Vec3::Z, PI / 4. + PI / 2.));
-
-    // 四个边框的坐标
-    let right = Transform::from_xyz((a + radius) / 2.0, 0.0, 0.0);
-    let left = Transform::from_xyz(-(a + radius) / 2.0, 0.0, 0.0);
-    let top = Transform::from_xyz(0.0, (b + radius) / 2.0, 0.0);
-    let bottom = Transform::from_xyz(0.0, -(b + radius) / 2.0, 0.0);
-    // 中心的坐标
-    let center = Transform::from_xyz(0.0, 0.0, thick / 2.);
-    let back = Transform::from_xyz(0.0, 0.0, -thick / 2.)
-        .with_rotation(Quat::from_axis_angle(Vec3::Y, PI));
-    // 加载一组的shape
-
-    let frames = [
-        (
-            meshes.add(Extrusion::new(CircularSector::new(radius, PI / 4.0), thick)),
-            right_top,
-        ),
-        (
-            meshes.add(Extrusion::new(CircularSector::new(radius, PI / 4.0), thick)),
-            right_bottom,
-        ),
-        (
-            meshes.add(Extrusion::new(CircularSector::new(radius, PI / 4.0), thick)),
-            left_top,
-        ),
-        (
-            meshes.add(Extrusion::new(CircularSector::new(radius, PI / 4.0), thick)),
-            left_bottom,
-        ),
-        (
-            meshes.add(Extrusion::new(
-                Rectangle::from_size(Vec2::new(a, radius)),
-                thick,
-            )),
-            top,
-        ),
-        (
-            meshes.add(Extrusion::new(
-                Rectangle::from_size(Vec2::new(a, radius)),
-                thick,
-            )),
-            bottom,
-        ),
-        (
-            meshes.add(Extrusion::new(
-                Rectangle::from_size(Vec2::new(radius, b)),
-                thick,
-            )),
-            left,
-        ),
-        (
-            meshes.add(Extrusion::new(
-                Rectangle::from_size(Vec2::new(radius, b)),
-                thick,
-            )),
-            right,
-        ),
-    ];
-
-    // 正面主要
-    let content = [(meshes.add(Rectangle::from_size(Vec2::new(a, b))), center)];
-    let back_side = [(meshes.add(Rectangle::from_size(Vec2::new(a, b))), back)];
-
-    (frames, content, back_side)
-}
+impl CardsConfig {
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
 
-#[derive(Component)]
-pub struct ClearOnFinish;
+    pub fn with_default_back(mut self, path: impl Into<String>) -> Self {
+        self.default_back = path.into();
+        self
+    }
 
-trait ClearOnFinishExt {
-    fn clear_on_finish(self) -> Self;
+    pub fn mesh_builder(&self) -> CardMeshBuilder {
+        CardMeshBuilder::new(self.width, self.height, self.radius, self.thick)
+    }
 }
 
-impl ClearOnFinishExt for bevy_tween::combinator::AnimationBuilder<'_> {
-    fn clear_on_finish(mut self) -> Self {
-        self.entity_commands().insert(ClearOnFinish);
-        self
+// 卡片本身 网格缓存 卡面 拖拽 多选 触屏和大图
+#[derive(Default)]
+pub struct CardsPlugin {
+    pub config: CardsConfig,
+}
+
+impl CardsPlugin {
+    pub fn new(config: CardsConfig) -> Self {
+        Self { config }
     }
 }
 
-pub fn clear_on_finish_system(
-    mut commands: Commands,
-    mut time_runner_finished: EventReader<bevy_tween::bevy_time_runner::TimeRunnerEnded>,
-    has_clear_on_finish: Query<Has<ClearOnFinish>>,
-    q_children: Query<&Children>,
-    q_tween: Query<(Entity, Has<bevy_tween::bevy_time_runner::TimeSpan>)>,
-) {
-    for time_runner in time_runner_finished.read() {
-        if has_clear_on_finish
-            .get(time_runner.time_runner)
-            .unwrap_or(false)
-        {
-            let Ok(children) = q_children.get(time_runner.time_runner) else {
-                continue;
-            };
-            for (entity, is_tween) in q_tween.iter_many(children) {
-                if is_tween {
-                    commands.entity(entity).despawn();
-                }
-            }
-        }
+impl Plugin for CardsPlugin {
+    fn build(&self, app: &mut App) {
+        // 卡面样式读取这里的路径 要先登记
+        app.insert_resource(self.config.clone())
+            .init_resource::<CardMeshCache>()
+            .init_resource::<HoverInspect>()
+            .add_plugins((CardFacePlugin, DragPlugin, SelectionPlugin, TouchPlugin))
+            .add_systems(
+                Update,
+                (inspect_action_system, hover_preview_system).chain(),
+            );
     }
 }
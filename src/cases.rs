@@ -1,4 +1,4 @@
-use crate::cards::deal_on_drop;
+use crate::cards::{deal_on_drop, tap_zone};
 use bevy::prelude::*;
 use std::f32::consts::PI;
 
@@ -27,6 +27,20 @@ pub enum CaseZoneType {
     JQ,
 }
 
+impl CaseZoneType {
+    // 语言文件里的 key
+    pub fn key(&self) -> &'static str {
+        match self {
+            CaseZoneType::Nothing => "zone.nothing",
+            CaseZoneType::Battle => "zone.battle",
+            CaseZoneType::Prepare => "zone.prepare",
+            CaseZoneType::Safe => "zone.safe",
+            CaseZoneType::Lx => "zone.lx",
+            CaseZoneType::JQ => "zone.jq",
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone, Copy)]
 pub struct CaseZone {
     pub zone_type: CaseZoneType,
@@ -58,7 +72,8 @@ pub fn render_case(
                         tr.clone(),
                         MeshMaterial3d(mal.clone()),
                     ))
-                    .observe(deal_on_drop);
+                    .observe(deal_on_drop)
+                    .observe(tap_zone);
             }
         });
 }
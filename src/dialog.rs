@@ -0,0 +1,704 @@
+use crate::actions::{ActionState, InputAction};
+use crate::focus::NavCommand;
+use crate::locale::{Localization, LocalizedFont, LocalizedText};
+use crate::theme::{
+    ButtonRole, FontRole, PanelRole, TextRole, ThemedButton, ThemedButtonText, ThemedPanel,
+    ThemedText, UiTheme,
+};
+use crate::{OnCancel, OnConfirm};
+use bevy::prelude::*;
+
+// 对话框的层级 叠加的对话框依次往上
+const DIALOG_Z_BASE: i32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogAction {
+    Confirm,
+    Cancel,
+    Custom(&'static str),
+}
+
+#[derive(Debug, Clone)]
+pub struct DialogButton {
+    pub label: String,
+    pub action: DialogAction,
+    // 快捷键走按键设置 可以改键
+    pub shortcut: Option<InputAction>,
+}
+
+impl DialogButton {
+    pub fn confirm(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            action: DialogAction::Confirm,
+            shortcut: Some(InputAction::DialogConfirm),
+        }
+    }
+
+    pub fn cancel(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            action: DialogAction::Cancel,
+            shortcut: Some(InputAction::DialogCancel),
+        }
+    }
+
+    pub fn custom(label: impl Into<String>, id: &'static str) -> Self {
+        Self {
+            label: label.into(),
+            action: DialogAction::Custom(id),
+            shortcut: None,
+        }
+    }
+
+    pub fn with_shortcut(mut self, action: InputAction) -> Self {
+        self.shortcut = Some(action);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum DialogBody {
+    #[default]
+    None,
+    // 卡图预览
+    CardPreview(Handle<Image>),
+    // 数字选择 上下方向键也可以调整
+    NumberPicker {
+        min: i32,
+        max: i32,
+        value: i32,
+    },
+    // 单选列表
+    ChoiceList(Vec<String>),
+}
+
+// 生成这个组件就会弹出对话框 结果通过 DialogResult 返回
+#[derive(Component, Debug, Clone)]
+pub struct Dialog {
+    pub title: String,
+    pub body: DialogBody,
+    pub buttons: Vec<DialogButton>,
+    pub width: f32,
+}
+
+impl Dialog {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            body: DialogBody::None,
+            buttons: vec![],
+            width: 500.0,
+        }
+    }
+
+    // 最常见的 确认/取消
+    pub fn confirm(title: impl Into<String>) -> Self {
+        Self::new(title)
+            .with_button(DialogButton::confirm("dialog.confirm"))
+            .with_button(DialogButton::cancel("dialog.cancel"))
+    }
+
+    pub fn with_body(mut self, body: DialogBody) -> Self {
+        self.body = body;
+        self
+    }
+
+    pub fn with_button(mut self, button: DialogButton) -> Self {
+        self.buttons.push(button);
+        self
+    }
+
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+}
+
+// 对话框关闭时的结果 以对话框实体为目标触发 也作为普通事件发送
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DialogResult {
+    pub dialog: Entity,
+    pub action: DialogAction,
+    // 数字选择的值
+    pub value: Option<i32>,
+    // 列表选中的下标
+    pub choice: Option<usize>,
+}
+
+// 当前打开的对话框 最后一个在最上层 只有它响应快捷键
+#[derive(Resource, Debug, Default)]
+pub struct DialogStack(Vec<Entity>);
+
+impl DialogStack {
+    pub fn top(&self) -> Option<Entity> {
+        self.0.last().copied()
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
+
+// 运行条件 没有对话框时才处理棋盘上的输入
+pub fn no_dialog_open(stack: Res<DialogStack>) -> bool {
+    !stack.is_open()
+}
+
+#[derive(Component, Debug, Default)]
+pub struct DialogState {
+    pub value: Option<i32>,
+    pub choice: Option<usize>,
+}
+
+#[derive(Component, Debug)]
+pub struct DialogButtonAction {
+    pub dialog: Entity,
+    pub action: DialogAction,
+}
+
+#[derive(Component, Debug)]
+pub struct DialogValueText;
+
+#[derive(Component, Debug)]
+pub struct DialogStep {
+    pub dialog: Entity,
+    pub delta: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct DialogChoice {
+    pub dialog: Entity,
+    pub index: usize,
+}
+
+pub fn build_dialog_system(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    theme: Res<UiTheme>,
+    mut stack: ResMut<DialogStack>,
+    dialogs: Query<(Entity, &Dialog), Added<Dialog>>,
+) {
+    for (entity, dialog) in dialogs.iter() {
+        let font = localization.font();
+        let mut state = DialogState::default();
+        match &dialog.body {
+            DialogBody::NumberPicker { value, .. } => state.value = Some(*value),
+            DialogBody::ChoiceList(choices) if !choices.is_empty() => state.choice = Some(0),
+            _ => {}
+        }
+        stack.0.push(entity);
+
+        // 铺满全屏的底板 挡住下面的 3D 棋盘
+        commands
+            .entity(entity)
+            .insert((
+                state,
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ThemedPanel(PanelRole::Backdrop),
+                GlobalZIndex(DIALOG_Z_BASE + stack.0.len() as i32),
+            ))
+            .with_children(|plane| {
+                plane
+                    .spawn((
+                        Node {
+                            width: Val::Px(dialog.width),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            padding: UiRect::all(Val::Px(theme.spacing.medium)),
+                            row_gap: Val::Px(theme.spacing.medium),
+                            ..default()
+                        },
+                        ThemedPanel(PanelRole::Dialog),
+                        BorderColor(Color::BLACK),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(localization.get(&dialog.title)),
+                            LocalizedText::new(dialog.title.clone()),
+                            TextFont {
+                                font: font.clone(),
+                                ..default()
+                            },
+                            ThemedText::new(FontRole::Title, TextRole::Dark),
+                        ));
+                        spawn_body(parent, entity, &dialog.body, &localization, &theme);
+
+                        // 按钮区域
+                        parent
+                            .spawn(Node {
+                                width: Val::Percent(100.0),
+                                height: Val::Px(50.0),
+                                justify_content: JustifyContent::SpaceEvenly,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            })
+                            .with_children(|b_zone| {
+                                for button in &dialog.buttons {
+                                    spawn_button(b_zone, entity, button, &localization);
+                                }
+                            });
+                    });
+            });
+    }
+}
+
+fn spawn_body(
+    parent: &mut ChildBuilder,
+    dialog: Entity,
+    body: &DialogBody,
+    localization: &Localization,
+    theme: &UiTheme,
+) {
+    let font = &localization.font();
+    match body {
+        DialogBody::None => {}
+        DialogBody::CardPreview(image) => {
+            parent.spawn((
+                ImageNode::new(image.clone()),
+                Node {
+                    width: Val::Px(150.0),
+                    height: Val::Px(210.0),
+                    ..default()
+                },
+            ));
+        }
+        DialogBody::NumberPicker { value, .. } => {
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(theme.spacing.large),
+                    align_items: AlignItems::Center,
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_step(row, dialog, -1, "-", font);
+                    row.spawn((
+                        DialogValueText,
+                        LocalizedFont,
+                        Text::new(value.to_string()),
+                        TextFont {
+                            font: font.clone(),
+                            ..default()
+                        },
+                        ThemedText::new(FontRole::Title, TextRole::Dark),
+                    ));
+                    spawn_step(row, dialog, 1, "+", font);
+                });
+        }
+        DialogBody::ChoiceList(choices) => {
+            parent
+                .spawn(Node {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(theme.spacing.small),
+                    ..default()
+                })
+                .with_children(|list| {
+                    for (index, choice) in choices.iter().enumerate() {
+                        list.spawn((
+                            Button,
+                            DialogChoice { dialog, index },
+                            ThemedButton::new(ButtonRole::Item),
+                            Node {
+                                width: Val::Percent(100.0),
+                                height: Val::Px(36.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((
+                                Text::new(localization.get(choice)),
+                                LocalizedText::new(choice.clone()),
+                                TextFont {
+                                    font: font.clone(),
+                                    ..default()
+                                },
+                                ThemedButtonText(FontRole::Body),
+                            ));
+                        })
+                        .observe(on_choice_click);
+                    }
+                });
+        }
+    }
+}
+
+fn spawn_step(
+    row: &mut ChildBuilder,
+    dialog: Entity,
+    delta: i32,
+    label: &str,
+    font: &Handle<Font>,
+) {
+    row.spawn((
+        Button,
+        DialogStep { dialog, delta },
+        ThemedButton::new(ButtonRole::Primary),
+        Node {
+            width: Val::Px(40.0),
+            height: Val::Px(40.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+    ))
+    .with_children(|btn| {
+        btn.spawn((
+            Text::new(label),
+            LocalizedFont,
+            TextFont {
+                font: font.clone(),
+                ..default()
+            },
+            ThemedButtonText(FontRole::Title),
+        ));
+    })
+    .observe(on_step_click);
+}
+
+fn spawn_button(
+    b_zone: &mut ChildBuilder,
+    dialog: Entity,
+    button: &DialogButton,
+    localization: &Localization,
+) {
+    let role = match button.action {
+        DialogAction::Cancel => ButtonRole::Danger,
+        _ => ButtonRole::Primary,
+    };
+    let mut entity = b_zone.spawn((
+        Button,
+        DialogButtonAction {
+            dialog,
+            action: button.action,
+        },
+        ThemedButton::new(role),
+        Node {
+            min_width: Val::Px(80.0),
+            height: Val::Px(40.0),
+            padding: UiRect::horizontal(Val::Px(8.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+    ));
+    match button.action {
+        DialogAction::Confirm => {
+            entity.insert(OnConfirm);
+        }
+        DialogAction::Cancel => {
+            entity.insert(OnCancel);
+        }
+        DialogAction::Custom(_) => {}
+    }
+    entity
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(localization.get(&button.label)),
+                LocalizedText::new(button.label.clone()),
+                TextFont {
+                    font: localization.font(),
+                    ..default()
+                },
+                ThemedButtonText(FontRole::Title),
+            ));
+        })
+        .observe(on_button_click);
+}
+
+fn close_dialog(
+    commands: &mut Commands,
+    stack: &mut DialogStack,
+    dialog: Entity,
+    action: DialogAction,
+    state: Option<&DialogState>,
+    results: &mut EventWriter<DialogResult>,
+) {
+    // 同一帧里点击和快捷键都可能来关 已经关掉的不能再弹出下面的对话框
+    if stack.top() != Some(dialog) {
+        return;
+    }
+    let result = DialogResult {
+        dialog,
+        action,
+        value: state.and_then(|s| s.value),
+        choice: state.and_then(|s| s.choice),
+    };
+    commands.trigger_targets(result, dialog);
+    results.send(result);
+    stack.0.pop();
+    // 先让观察者处理完结果再删除
+    commands.queue(move |world: &mut World| {
+        if let Ok(entity) = world.get_entity_mut(dialog) {
+            entity.despawn_recursive();
+        }
+    });
+}
+
+fn on_button_click(
+    click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut stack: ResMut<DialogStack>,
+    buttons: Query<&DialogButtonAction>,
+    states: Query<&DialogState>,
+    mut results: EventWriter<DialogResult>,
+) {
+    let Ok(button) = buttons.get(click.entity()) else {
+        return;
+    };
+    // 只有最上层的对话框可以操作
+    if stack.top() != Some(button.dialog) {
+        return;
+    }
+    close_dialog(
+        &mut commands,
+        &mut stack,
+        button.dialog,
+        button.action,
+        states.get(button.dialog).ok(),
+        &mut results,
+    );
+}
+
+fn on_step_click(
+    click: Trigger<Pointer<Click>>,
+    steps: Query<&DialogStep>,
+    dialogs: Query<&Dialog>,
+    mut states: Query<&mut DialogState>,
+) {
+    let Ok(step) = steps.get(click.entity()) else {
+        return;
+    };
+    if let (Ok(dialog), Ok(mut state)) = (dialogs.get(step.dialog), states.get_mut(step.dialog)) {
+        step_value(&dialog.body, &mut state, step.delta);
+    }
+}
+
+fn on_choice_click(
+    click: Trigger<Pointer<Click>>,
+    choices: Query<&DialogChoice>,
+    mut states: Query<&mut DialogState>,
+) {
+    let Ok(choice) = choices.get(click.entity()) else {
+        return;
+    };
+    if let Ok(mut state) = states.get_mut(choice.dialog) {
+        state.choice = Some(choice.index);
+    }
+}
+
+fn step_value(body: &DialogBody, state: &mut DialogState, delta: i32) {
+    match body {
+        DialogBody::NumberPicker { min, max, .. } => {
+            if let Some(value) = state.value {
+                state.value = Some((value + delta).clamp(*min, *max));
+            }
+        }
+        DialogBody::ChoiceList(choices) => {
+            if let Some(choice) = state.choice {
+                let last = choices.len().saturating_sub(1) as i32;
+                state.choice = Some((choice as i32 + delta).clamp(0, last) as usize);
+            }
+        }
+        _ => {}
+    }
+}
+
+// 按钮的快捷键 只作用于最上层的对话框
+// 上下 确认 取消只看 NavCommand 方向键和手柄都由焦点系统先转换
+pub fn dialog_keyboard_system(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut nav: EventReader<NavCommand>,
+    mut stack: ResMut<DialogStack>,
+    mut dialogs: Query<(&Dialog, &mut DialogState)>,
+    mut results: EventWriter<DialogResult>,
+) {
+    let nav: Vec<NavCommand> = nav.read().copied().collect();
+    // 刚打开的对话框不处理打开它的那次确认
+    if stack.is_changed() {
+        return;
+    }
+    let Some(top) = stack.top() else {
+        return;
+    };
+    let Ok((dialog, mut state)) = dialogs.get_mut(top) else {
+        return;
+    };
+    // 列表向下是下一个 数字向上是增加
+    let delta = match &dialog.body {
+        DialogBody::ChoiceList(_) => 1,
+        _ => -1,
+    };
+    for command in &nav {
+        match command {
+            NavCommand::Up => step_value(&dialog.body, &mut state, -delta),
+            NavCommand::Down => step_value(&dialog.body, &mut state, delta),
+            _ => {}
+        }
+    }
+
+    let pressed = dialog.buttons.iter().find(|button| {
+        button
+            .shortcut
+            .is_some_and(|action| actions.just_pressed(action))
+            || (nav.contains(&NavCommand::Select) && button.action == DialogAction::Confirm)
+            || (nav.contains(&NavCommand::Cancel) && button.action == DialogAction::Cancel)
+    });
+    if let Some(button) = pressed {
+        close_dialog(
+            &mut commands,
+            &mut stack,
+            top,
+            button.action,
+            Some(&state),
+            &mut results,
+        );
+    }
+}
+
+// 数字和选中项变化后刷新显示
+pub fn dialog_display_system(
+    states: Query<(Entity, &DialogState), Changed<DialogState>>,
+    mut value_texts: Query<(&mut Text, &Parent), With<DialogValueText>>,
+    parents: Query<&Parent>,
+    mut choices: Query<(&DialogChoice, &mut ThemedButton)>,
+) {
+    for (dialog, state) in states.iter() {
+        if let Some(value) = state.value {
+            for (mut text, parent) in value_texts.iter_mut() {
+                if parents.iter_ancestors(parent.get()).any(|e| e == dialog) {
+                    text.0 = value.to_string();
+                }
+            }
+        }
+        for (choice, mut button) in choices.iter_mut() {
+            if choice.dialog != dialog {
+                continue;
+            }
+            button.selected = state.choice == Some(choice.index);
+        }
+    }
+}
+
+pub struct DialogPlugin;
+
+impl Plugin for DialogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DialogStack>()
+            .add_event::<DialogResult>()
+            .add_systems(
+                Update,
+                (
+                    build_dialog_system,
+                    dialog_keyboard_system,
+                    dialog_display_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cases::{CaseZone, CaseZoneType};
+    use crate::focus::{apply_nav_commands, focus_input_system, FocusCursor};
+    use crate::inspect::HoverInspect;
+    use crate::presentation::PresentationQueue;
+    use bevy::input::InputPlugin;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .init_resource::<DialogStack>()
+            .init_resource::<ActionState>()
+            .init_resource::<HoverInspect>()
+            .init_resource::<PresentationQueue>()
+            .insert_resource(FocusCursor {
+                active: true,
+                ..default()
+            })
+            .add_event::<NavCommand>()
+            .add_event::<DialogResult>()
+            .add_systems(
+                Update,
+                (
+                    focus_input_system,
+                    dialog_keyboard_system,
+                    apply_nav_commands,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    fn nav(app: &mut App, command: NavCommand) {
+        app.world_mut().send_event(command);
+        app.update();
+    }
+
+    fn value(app: &App, dialog: Entity) -> Option<i32> {
+        app.world()
+            .get::<DialogState>(dialog)
+            .and_then(|state| state.value)
+    }
+
+    #[test]
+    fn nav_commands_step_the_dialog_once_and_leave_the_cursor_alone() {
+        let mut app = app();
+        // 对话框关着时方向键会把焦点移到这个场地上
+        app.world_mut().spawn((
+            CaseZone {
+                zone_type: CaseZoneType::Safe,
+                transform: Transform::default(),
+                num: 0,
+            },
+            GlobalTransform::default(),
+        ));
+        let dialog = app
+            .world_mut()
+            .spawn((
+                Dialog::confirm("test").with_body(DialogBody::NumberPicker {
+                    min: 0,
+                    max: 10,
+                    value: 5,
+                }),
+                DialogState {
+                    value: Some(5),
+                    choice: None,
+                },
+            ))
+            .id();
+        app.world_mut().resource_mut::<DialogStack>().0.push(dialog);
+        app.update();
+
+        nav(&mut app, NavCommand::Up);
+        assert_eq!(value(&app, dialog), Some(6));
+        nav(&mut app, NavCommand::Down);
+        nav(&mut app, NavCommand::Down);
+        assert_eq!(value(&app, dialog), Some(4));
+        assert_eq!(app.world().resource::<FocusCursor>().target, None);
+
+        // 取消关掉对话框 同一次取消不会再交给棋盘
+        nav(&mut app, NavCommand::Cancel);
+        assert!(!app.world().resource::<DialogStack>().is_open());
+        let cursor = app.world().resource::<FocusCursor>();
+        assert_eq!(cursor.target, None);
+        assert_eq!(cursor.held, None);
+
+        // 关掉之后方向键回到棋盘
+        nav(&mut app, NavCommand::Up);
+        assert!(app.world().resource::<FocusCursor>().target.is_some());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::cards::{Dragging, Setted};
+use crate::cases::{CasePlane, CaseZone, CaseZoneType};
+use crate::selection::DragFollower;
+use crate::settings::AnimationSettings;
+use bevy::picking::backend::ray::{RayId, RayMap};
+use bevy::picking::focus::HoverMap;
+use bevy::prelude::*;
+
+// 回弹的弹簧按固定步长积分 卡顿的一帧拆成几小步 不会越积越大
+const RELEASE_STEP: f32 = 1.0 / 120.0;
+// 卡得太久时只补这么多时间 剩下的直接丢掉
+const RELEASE_MAX_DT: f32 = 0.1;
+
+// 拖拽时卡片投影的平面 用实体的位置和朝向表示
+// up 是法线 right 和 forward 是平面上的两个轴
+#[derive(Debug, Clone, Copy)]
+pub struct DragPlane {
+    pub origin: Vec3,
+    pub normal: Dir3,
+    pub right: Dir3,
+    pub forward: Dir3,
+}
+
+impl DragPlane {
+    pub fn from_transform(transform: &GlobalTransform) -> Self {
+        Self {
+            origin: transform.translation(),
+            normal: transform.up(),
+            right: transform.right(),
+            forward: transform.forward(),
+        }
+    }
+
+    // 经过一点 正对着射线的平面
+    pub fn facing(origin: Vec3, ray: Ray3d) -> Self {
+        let normal = -ray.direction;
+        let right = Dir3::new(normal.any_orthonormal_vector()).unwrap_or(Dir3::X);
+        let forward = Dir3::new(normal.cross(*right)).unwrap_or(Dir3::Y);
+        Self {
+            origin,
+            normal,
+            right,
+            forward,
+        }
+    }
+
+    // 沿法线平移
+    pub fn offset(mut self, distance: f32) -> Self {
+        self.origin += *self.normal * distance;
+        self
+    }
+
+    pub fn intersect(&self, ray: Ray3d) -> Option<Vec3> {
+        ray.intersect_plane(self.origin, InfinitePlane3d::new(*self.normal))
+            .map(|distance| ray.get_point(distance))
+    }
+
+    // 平面上的二维坐标
+    pub fn coords(&self, point: Vec3) -> Vec2 {
+        let local = point - self.origin;
+        Vec2::new(local.dot(*self.right), local.dot(*self.forward))
+    }
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct DragConfig {
+    // 棋盘在棋盘平面上的范围 鼠标指到这里面时卡片落到棋盘上
+    pub board_bounds: Rect,
+    // 离开棋盘多远开始从手牌平面过渡
+    pub lift_range: f32,
+    // 在棋盘上拖动时离棋盘的高度
+    pub board_lift: f32,
+    // 跟随指针的快慢 0 表示直接贴在指针上
+    pub follow_stiffness: f32,
+    // 每单位速度倾斜的弧度
+    pub tilt_per_speed: f32,
+    pub max_tilt: f32,
+    // 指针在场地上时往场地中心吸的程度 0 不吸附 1 直接贴到中心
+    pub snap_strength: f32,
+    // 松手后回弹的弹簧 阻尼小于 2 * sqrt(stiffness) 时会稍微冲过头
+    pub release_stiffness: f32,
+    pub release_damping: f32,
+}
+
+impl Default for DragConfig {
+    fn default() -> Self {
+        Self {
+            board_bounds: Rect::new(-12.0, -8.6, 12.0, 8.6),
+            lift_range: 3.0,
+            board_lift: 0.5,
+            follow_stiffness: 20.0,
+            tilt_per_speed: 0.03,
+            max_tilt: 0.35,
+            snap_strength: 0.6,
+            release_stiffness: 80.0,
+            release_damping: 15.0,
+        }
+    }
+}
+
+// 0 在手牌平面 1 在棋盘上 中间平滑过渡
+fn board_weight(config: &DragConfig, board_point: Vec2) -> f32 {
+    let bounds = config.board_bounds;
+    let outside = (bounds.min - board_point)
+        .max(board_point - bounds.max)
+        .max(Vec2::ZERO)
+        .length();
+    let t = 1.0 - (outside / config.lift_range.max(f32::EPSILON)).min(1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// 拖拽中的卡片的目标跟着指针的射线走 用开始拖拽时的那个相机
+// 没有手牌平面时保持卡片当前的深度 卡片本身由 drag_follow_system 移过去
+// 一起拖的卡片的目标保持开始时的相对位置
+#[allow(clippy::type_complexity)]
+pub fn move_on_drag<C>() -> impl Fn(
+    Trigger<Pointer<Drag>>,
+    Query<(&Transform, &mut Dragging, Option<&DragFollower>), Without<Setted>>,
+    Res<RayMap>,
+    Res<DragConfig>,
+    Query<&GlobalTransform, With<C>>,
+    Query<&GlobalTransform, With<CasePlane>>,
+)
+where
+    C: Component,
+{
+    move |drag, mut cards, ray_map, config, hand_planes, board_planes| {
+        let Ok((transform, mut dragging, _)) = cards.get_mut(drag.entity()) else {
+            return;
+        };
+        let Some(ray) = ray_map
+            .map()
+            .get(&RayId::new(dragging.camera, drag.pointer_id))
+            .copied()
+        else {
+            return;
+        };
+        let hand = hand_planes
+            .iter()
+            .next()
+            .map_or(DragPlane::facing(transform.translation, ray), |plane| {
+                DragPlane::from_transform(plane)
+            });
+        let Some(hand_point) = hand.intersect(ray) else {
+            return;
+        };
+        let board = board_planes.iter().next().map(DragPlane::from_transform);
+        let board_point = board.and_then(|board| {
+            let point = board.intersect(ray)?;
+            let lifted = board.offset(config.board_lift).intersect(ray)?;
+            Some((board.coords(point), lifted))
+        });
+        let target = match board_point {
+            Some((coords, lifted)) => hand_point.lerp(lifted, board_weight(&config, coords)),
+            None => hand_point,
+        };
+        dragging.target = target;
+        let leader = drag.entity();
+        for (_, mut dragging, follower) in cards.iter_mut() {
+            if let Some(follower) = follower.filter(|follower| follower.leader == leader) {
+                dragging.target = target + follower.offset;
+            }
+        }
+    }
+}
+
+fn approach(stiffness: f32, dt: f32) -> f32 {
+    if stiffness <= 0.0 {
+        1.0
+    } else {
+        1.0 - (-stiffness * dt).exp()
+    }
+}
+
+// 按速度倾斜 往右拖时右边沉下去 往上拖时上边沉下去
+fn tilt(config: &DragConfig, velocity: Vec3) -> Quat {
+    let tilt = (velocity.truncate() * config.tilt_per_speed)
+        .clamp(Vec2::splat(-config.max_tilt), Vec2::splat(config.max_tilt));
+    Quat::from_rotation_y(tilt.x) * Quat::from_rotation_x(-tilt.y)
+}
+
+// 拖拽中的卡片平滑地追向目标 指针在场地上时吸向场地中心
+pub fn drag_follow_system(
+    time: Res<Time>,
+    config: Res<DragConfig>,
+    settings: Res<AnimationSettings>,
+    hover_map: Res<HoverMap>,
+    zones: Query<(&CaseZone, &GlobalTransform)>,
+    mut cards: Query<(&mut Transform, &mut Dragging, Has<DragFollower>)>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    for (mut transform, mut dragging, follower) in cards.iter_mut() {
+        // 悬停的场地是领头那张的 跟着走的不吸附
+        let hits = hover_map.get(&dragging.pointer).filter(|_| !follower);
+        let zone = hits.and_then(|hits| {
+            hits.keys()
+                .filter_map(|entity| zones.get(*entity).ok())
+                .find(|(zone, _)| !matches!(zone.zone_type, CaseZoneType::Nothing))
+        });
+        let target = match zone {
+            Some((_, zone)) => {
+                let center = zone.translation() + *zone.back() * config.board_lift;
+                dragging
+                    .target
+                    .lerp(center, config.snap_strength.clamp(0.0, 1.0))
+            }
+            None => dragging.target,
+        };
+        let previous = transform.translation;
+        transform.translation = previous.lerp(target, approach(config.follow_stiffness, dt));
+        // 速度也平滑一下 倾斜不会抖
+        let measured = (transform.translation - previous) / dt;
+        dragging.velocity = dragging.velocity.lerp(measured, approach(15.0, dt));
+        transform.rotation = if settings.reduced_motion {
+            dragging.rotation
+        } else {
+            tilt(&config, dragging.velocity) * dragging.rotation
+        };
+    }
+}
+
+// 松手后带着当时的速度弹回目标 同时摆正和恢复大小
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DragRelease {
+    pub target: Vec3,
+    pub velocity: Vec3,
+    pub rotation: Quat,
+}
+
+pub fn drag_release_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<DragConfig>,
+    mut cards: Query<(Entity, &mut Transform, &mut DragRelease), Without<Dragging>>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    let dt = dt.min(RELEASE_MAX_DT);
+    let steps = (dt / RELEASE_STEP).ceil().max(1.0);
+    let step = dt / steps;
+    for (entity, mut transform, mut release) in cards.iter_mut() {
+        let mut offset = release.target - transform.translation;
+        for _ in 0..steps as u32 {
+            let acceleration =
+                offset * config.release_stiffness - release.velocity * config.release_damping;
+            release.velocity += acceleration * step;
+            transform.translation += release.velocity * step;
+            offset = release.target - transform.translation;
+        }
+        let k = approach(12.0, dt);
+        transform.rotation = transform.rotation.slerp(release.rotation, k);
+        transform.scale = transform.scale.lerp(Vec3::ONE, k);
+        if offset.length() < 0.01 && release.velocity.length() < 0.05 {
+            transform.translation = release.target;
+            transform.rotation = release.rotation;
+            transform.scale = Vec3::ONE;
+            commands.entity(entity).remove::<DragRelease>();
+        }
+    }
+}
+
+pub struct DragPlugin;
+
+impl Plugin for DragPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DragConfig>()
+            .add_systems(Update, (drag_follow_system, drag_release_system));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::camera_controller::CameraController;
+use crate::camera_rig::{CameraRig, RigPose};
+use crate::lifecycle::AnimationLifecycleExt;
+use crate::settings::{AnimationCategory, AnimationCategoryExt, AnimationSettings};
+use crate::MainCamera;
+use bevy::color::palettes::css::{WHITE, YELLOW};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_tween::combinator::{sequence, tween};
+use bevy_tween::interpolate::{scale, sprite_color, translation_to};
+use bevy_tween::prelude::*;
+use bevy_tween::tween::AnimationTarget;
+use rand::prelude::*;
+use std::time::Duration;
+
+// 特效用到的素材 相对 assets 目录
+#[derive(Resource, Debug, Clone)]
+pub struct EffectsConfig {
+    pub explosion_image: String,
+}
+
+impl Default for EffectsConfig {
+    fn default() -> Self {
+        Self {
+            explosion_image: "circle.png".to_string(),
+        }
+    }
+}
+
+// 特效播放时镜头推过去的距离和停留时间
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraFocus {
+    pub distance: f32,
+    pub pitch: f32,
+    pub hold_secs: f32,
+}
+
+impl Default for CameraFocus {
+    fn default() -> Self {
+        Self {
+            distance: 14.0,
+            pitch: 0.2,
+            hold_secs: 0.8,
+        }
+    }
+}
+
+// 每个特效的配置 没有登记的特效只播放本身
+#[derive(Debug, Clone, Default)]
+pub struct EffectConfig {
+    pub focus: Option<CameraFocus>,
+}
+
+impl EffectConfig {
+    pub fn with_focus(mut self, focus: CameraFocus) -> Self {
+        self.focus = Some(focus);
+        self
+    }
+}
+
+// 特效名字到配置的表 演出只发名字 具体怎么表现查这里
+#[derive(Resource, Debug, Clone)]
+pub struct EffectRegistry {
+    effects: HashMap<&'static str, EffectConfig>,
+}
+
+impl Default for EffectRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            effects: HashMap::new(),
+        };
+        // 登场的爆炸推近看一眼
+        registry.register(
+            "boom",
+            EffectConfig::default().with_focus(CameraFocus::default()),
+        );
+        registry
+    }
+}
+
+impl EffectRegistry {
+    pub fn register(&mut self, name: &'static str, config: EffectConfig) -> &mut Self {
+        self.effects.insert(name, config);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EffectConfig> {
+        self.effects.get(name)
+    }
+
+    // 单独打开或关闭某个特效的镜头跟随
+    pub fn set_focus(&mut self, name: &'static str, focus: Option<CameraFocus>) {
+        self.effects.entry(name).or_default().focus = focus;
+    }
+}
+
+// 演出开始播放一个特效 target 是特效发生的位置
+#[derive(Event, Debug, Clone, Copy)]
+pub struct EffectTriggered {
+    pub name: &'static str,
+    pub target: Option<Entity>,
+}
+
+// 配置了镜头跟随的特效 镜头推到目标上 停留后回到原来的视角
+pub fn focus_camera_on_effect(
+    trigger: Trigger<EffectTriggered>,
+    registry: Res<EffectRegistry>,
+    settings: Res<AnimationSettings>,
+    targets: Query<&GlobalTransform>,
+    mut rigs: Query<(&Transform, &mut CameraRig, Option<&CameraController>)>,
+) {
+    if !settings.camera_focus() {
+        return;
+    }
+    let EffectTriggered { name, target } = *trigger.event();
+    let Some(focus) = registry.get(name).and_then(|config| config.focus) else {
+        return;
+    };
+    let Some(point) = target.and_then(|target| targets.get(target).ok()) else {
+        return;
+    };
+    // 卡片可能还在空中 只看它在棋盘上的位置
+    let point = point.translation().truncate().extend(0.0);
+    for (transform, mut rig, controller) in rigs.iter_mut() {
+        if controller.is_some_and(|controller| controller.enabled) {
+            continue;
+        }
+        let pose = RigPose::new(point, focus.distance, focus.pitch);
+        rig.focus_on(pose, focus.hold_secs, transform);
+    }
+}
+
+// 动画里发出的特效事件 爆炸和镜头抖动
+pub fn effect_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<EffectsConfig>,
+    settings: Res<AnimationSettings>,
+    mut event: EventReader<TweenEvent<&'static str>>,
+    query: Query<(Entity, &Transform), With<MainCamera>>,
+) {
+    event.read().for_each(|event| match event.data {
+        "boom" => {
+            info!("Boom!");
+            let entity = AnimationTarget.into_target();
+            // 减少动效时不放大 只变色
+            let end_scale = if settings.large_scaling() {
+                Vec3::new(15., 15., 0.)
+            } else {
+                Vec3::new(1., 1., 0.)
+            };
+            commands
+                .spawn((
+                    Sprite {
+                        image: asset_server.load(&config.explosion_image),
+                        ..default()
+                    },
+                    // todo 这里的值要变
+                    Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)),
+                    AnimationTarget,
+                ))
+                .animation()
+                .category(AnimationCategory::Effects)
+                .despawn_on_finish()
+                .insert_tween_here(
+                    Duration::from_secs_f32(10.0),
+                    EaseKind::QuadraticOut,
+                    (
+                        entity.with(scale(Vec3::new(1., 1., 0.), end_scale)),
+                        entity.with(sprite_color(
+                            into_color(WHITE.with_alpha(1.)),
+                            into_color(YELLOW.with_alpha(1.)),
+                        )),
+                    ),
+                );
+        }
+        "shark2" => {
+            if !settings.camera_shake() {
+                return;
+            }
+            // todo 镜头动
+            let mut rng = rand::rng();
+            let dx: f32 = rng.random_range(-5.0..=5.0);
+            let dy: f32 = rng.random_range(-5.0..=5.0);
+
+            if let Ok((entity, trans)) = query.get_single() {
+                let entity_a = AnimationTarget.into_target();
+                let mut target_state = entity_a.state(trans.translation);
+                commands
+                    .entity(entity)
+                    .insert(AnimationTarget)
+                    .animation()
+                    .category(AnimationCategory::Effects)
+                    .repeat(Repeat::Times {
+                        times: 7,
+                        times_repeated: 1,
+                    })
+                    .insert(sequence((tween(
+                        Duration::from_secs_f32(0.1),
+                        EaseKind::ExponentialOut,
+                        target_state.with(translation_to(Vec3::new(dx, dy, trans.translation.z))),
+                    ),)));
+            }
+        }
+        _ => {}
+    });
+}
+
+fn into_color<T: Into<bevy::color::Srgba>>(color: T) -> Color {
+    Color::Srgba(color.into())
+}
+
+// 特效的表现和镜头跟随
+#[derive(Default)]
+pub struct EffectsPlugin {
+    pub config: EffectsConfig,
+}
+
+impl EffectsPlugin {
+    pub fn new(config: EffectsConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .init_resource::<EffectRegistry>()
+            .add_observer(focus_camera_on_effect)
+            .add_systems(Update, effect_system);
+    }
+}
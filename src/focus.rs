@@ -0,0 +1,287 @@
+use crate::actions::{ActionState, InputAction};
+use crate::cards::{Card, Setted};
+use crate::cases::{CaseZone, CaseZoneType};
+use crate::dialog::{dialog_keyboard_system, DialogStack};
+use crate::inspect::HoverInspect;
+use crate::play::{PendingPlay, PlayRequested};
+use crate::presentation::PresentationQueue;
+use crate::prompt::{ChoicePrompt, Selectable};
+use crate::theme::UiTheme;
+use bevy::input::mouse::AccumulatedMouseMotion;
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+
+// 不用鼠标时的操作 键盘和手柄都先转换成它 测试时也可以直接发送
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavCommand {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Cancel,
+}
+
+impl NavCommand {
+    fn direction(&self) -> Option<Vec2> {
+        match self {
+            NavCommand::Up => Some(Vec2::Y),
+            NavCommand::Down => Some(Vec2::NEG_Y),
+            NavCommand::Left => Some(Vec2::NEG_X),
+            NavCommand::Right => Some(Vec2::X),
+            _ => None,
+        }
+    }
+}
+
+// 焦点光标 target 是当前指着的卡片或场地 held 是选中等待放到场地上的手卡
+#[derive(Resource, Debug, Default)]
+pub struct FocusCursor {
+    // 用键盘或手柄操作时才显示 动鼠标后隐藏
+    pub active: bool,
+    pub target: Option<Entity>,
+    pub held: Option<Entity>,
+}
+
+impl FocusCursor {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+const NAV_ACTIONS: [(InputAction, NavCommand); 6] = [
+    (InputAction::NavUp, NavCommand::Up),
+    (InputAction::NavDown, NavCommand::Down),
+    (InputAction::NavLeft, NavCommand::Left),
+    (InputAction::NavRight, NavCommand::Right),
+    (InputAction::Select, NavCommand::Select),
+    (InputAction::Cancel, NavCommand::Cancel),
+];
+
+// 切换键进入或退出键盘操作 进入后方向键移动焦点而不是平移镜头
+// 手柄按任意键直接进入 鼠标移动时退出 对话框打开时总是转换给对话框
+pub fn focus_input_system(
+    actions: Res<ActionState>,
+    stack: Res<DialogStack>,
+    gamepads: Query<&Gamepad>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mut cursor: ResMut<FocusCursor>,
+    mut commands: EventWriter<NavCommand>,
+) {
+    if actions.just_pressed(InputAction::ToggleFocus) {
+        cursor.active = !cursor.active;
+    }
+    let gamepad_used = gamepads
+        .iter()
+        .any(|gamepad| gamepad.get_just_pressed().next().is_some());
+    if gamepad_used {
+        cursor.active = true;
+    }
+    if !cursor.active && !stack.is_open() {
+        return;
+    }
+    let pressed: Vec<NavCommand> = NAV_ACTIONS
+        .iter()
+        .filter(|(action, _)| actions.just_pressed(*action))
+        .map(|(_, command)| *command)
+        .collect();
+    if !pressed.is_empty() {
+        commands.send_batch(pressed);
+    } else if !gamepad_used && mouse_motion.delta != Vec2::ZERO {
+        cursor.active = false;
+    }
+}
+
+// 往某个方向找最近的目标 偏离方向越多越不优先
+fn nearest_in_direction(
+    from: Vec2,
+    direction: Vec2,
+    candidates: &[(Entity, Vec2)],
+) -> Option<Entity> {
+    candidates
+        .iter()
+        .filter_map(|(entity, position)| {
+            let offset = *position - from;
+            let along = offset.dot(direction);
+            if along <= 0.01 {
+                return None;
+            }
+            let across = offset.perp_dot(direction).abs();
+            Some((*entity, along + across * 2.0))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+fn nearest_to(from: Vec2, candidates: &[(Entity, Vec2)]) -> Option<Entity> {
+    candidates
+        .iter()
+        .min_by(|a, b| a.1.distance(from).total_cmp(&b.1.distance(from)))
+        .map(|(entity, _)| *entity)
+}
+
+// 处理导航操作 没有选中手卡时在手卡和场地之间移动 选中后只在场地之间移动
+// 在场地上确认时和拖拽一样发出 PlayRequested 之后走同样的确认对话框
+// 有选择进行中时只在可选对象之间移动 确认就是点选
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn apply_nav_commands(
+    mut commands: Commands,
+    mut nav: EventReader<NavCommand>,
+    mut cursor: ResMut<FocusCursor>,
+    mut inspect: ResMut<HoverInspect>,
+    hand: Query<(Entity, &GlobalTransform), (With<Card>, Without<Setted>, Without<PendingPlay>)>,
+    zones: Query<(Entity, &CaseZone, &GlobalTransform)>,
+    selectables: Query<(Entity, &Selectable, &GlobalTransform)>,
+    mut prompts: Query<&mut ChoicePrompt>,
+    stack: Res<DialogStack>,
+    presentation: Res<PresentationQueue>,
+) {
+    // 对话框打开或刚关掉时交给对话框 避免同一次确认既关掉对话框又选中
+    if stack.is_open() || stack.is_changed() || presentation.is_blocking() {
+        nav.clear();
+        return;
+    }
+    let prompt_active = !prompts.is_empty();
+    // 选中的手卡已经不在手里了
+    if cursor.held.is_some_and(|card| !hand.contains(card)) {
+        cursor.held = None;
+    }
+    let zone_targets: Vec<(Entity, Vec2)> = zones
+        .iter()
+        .filter(|(_, zone, _)| !matches!(zone.zone_type, CaseZoneType::Nothing))
+        .map(|(entity, _, transform)| (entity, transform.translation().truncate()))
+        .collect();
+    let mut candidates = vec![];
+    if prompt_active {
+        candidates.extend(
+            selectables
+                .iter()
+                .map(|(entity, _, transform)| (entity, transform.translation().truncate())),
+        );
+    } else {
+        if cursor.held.is_none() {
+            candidates.extend(
+                hand.iter()
+                    .map(|(entity, transform)| (entity, transform.translation().truncate())),
+            );
+        }
+        candidates.extend(zone_targets.iter().copied());
+    }
+    let position = |entity: Entity| {
+        candidates
+            .iter()
+            .find(|(candidate, _)| *candidate == entity)
+            .map(|(_, position)| *position)
+    };
+    // 目标消失或不在候选里时重新找一个
+    if cursor.target.and_then(position).is_none() {
+        cursor.target = None;
+    }
+
+    for command in nav.read() {
+        let current = cursor.target.and_then(position);
+        if let Some(direction) = command.direction() {
+            cursor.target = match current {
+                Some(from) => nearest_in_direction(from, direction, &candidates).or(cursor.target),
+                None => nearest_to(Vec2::new(0.0, -20.0), &candidates),
+            };
+            continue;
+        }
+        let Some(target) = cursor.target else {
+            continue;
+        };
+        match command {
+            NavCommand::Select if prompt_active => {
+                let selected = selectables.get(target).ok().and_then(|(_, selectable, _)| {
+                    let prompt = prompts.get_mut(selectable.prompt).ok()?;
+                    let index = prompt.index_of(target)?;
+                    Some((prompt, index))
+                });
+                if let Some((mut prompt, index)) = selected {
+                    prompt.toggle(index);
+                }
+            }
+            NavCommand::Select => match cursor.held {
+                Some(card) if zones.contains(target) => {
+                    commands.trigger(PlayRequested { card, zone: target });
+                    cursor.held = None;
+                }
+                Some(card) if card == target => cursor.held = None,
+                Some(_) => {}
+                None if hand.contains(target) => {
+                    cursor.held = Some(target);
+                    // 直接跳到最近的场地
+                    let from = position(target).unwrap_or_default();
+                    cursor.target = nearest_to(from, &zone_targets).or(Some(target));
+                }
+                None => {}
+            },
+            NavCommand::Cancel => {
+                if let Some(card) = cursor.held.take() {
+                    cursor.target = Some(card);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 焦点在手卡上时和悬停一样显示大图
+    match cursor.target.filter(|target| hand.contains(*target)) {
+        Some(card) if cursor.active => inspect.begin(card),
+        _ => {
+            if let Some(card) = inspect.card().filter(|card| Some(*card) == cursor.target) {
+                inspect.end(card);
+            }
+        }
+    }
+}
+
+// 焦点画一个框 选中的手卡画强调色
+pub fn focus_highlight_system(
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+    cursor: Res<FocusCursor>,
+    targets: Query<(&GlobalTransform, Option<&Aabb>)>,
+) {
+    if !cursor.active {
+        return;
+    }
+    let mut draw = |entity: Entity, color: Color| {
+        let Ok((transform, aabb)) = targets.get(entity) else {
+            return;
+        };
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let size = aabb.map_or(Vec2::splat(2.0), |aabb| {
+            Vec3::from(aabb.half_extents).truncate() * 2.0
+        }) * scale.truncate();
+        gizmos.rect(
+            Isometry3d::new(translation + rotation * Vec3::Z * 0.1, rotation),
+            size + Vec2::splat(0.2),
+            color,
+        );
+    };
+    if let Some(held) = cursor.held {
+        draw(held, theme.palette.accent);
+    }
+    if let Some(target) = cursor.target {
+        draw(target, theme.palette.text_light);
+    }
+}
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FocusCursor>()
+            .add_event::<NavCommand>()
+            .add_systems(
+                Update,
+                (
+                    focus_input_system.before(dialog_keyboard_system),
+                    apply_nav_commands.after(dialog_keyboard_system),
+                    focus_highlight_system,
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,185 @@
+use crate::cards::CardLocation;
+use crate::match_log::{LogEntry, LogKind, MatchLogExt};
+use bevy::prelude::*;
+
+// 对局中的玩家 seat 是出手顺序
+#[derive(Component, Debug, Clone)]
+pub struct Player {
+    pub name: String,
+    pub seat: usize,
+}
+
+// 生命值 也当作分数用
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Life(pub i32);
+
+// Lx / JQ 两种资源
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Resources {
+    pub lx: u32,
+    pub jq: u32,
+}
+
+// 牌库 手牌 弃牌堆的数量
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZoneCounts {
+    pub deck: u32,
+    pub hand: u32,
+    pub graveyard: u32,
+}
+
+// 卡片属于哪个玩家
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Owner(pub Entity);
+
+#[derive(Bundle)]
+pub struct PlayerBundle {
+    pub player: Player,
+    pub life: Life,
+    pub resources: Resources,
+    pub counts: ZoneCounts,
+}
+
+impl PlayerBundle {
+    pub fn new(name: impl Into<String>, seat: usize, life: i32) -> Self {
+        Self {
+            player: Player {
+                name: name.into(),
+                seat,
+            },
+            life: Life(life),
+            resources: Resources::default(),
+            counts: ZoneCounts::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Phase {
+    #[default]
+    Draw,
+    Main,
+    Battle,
+    End,
+}
+
+impl Phase {
+    // 语言文件里的 key
+    pub fn key(&self) -> &'static str {
+        match self {
+            Phase::Draw => "phase.draw",
+            Phase::Main => "phase.main",
+            Phase::Battle => "phase.battle",
+            Phase::End => "phase.end",
+        }
+    }
+
+    pub fn next(&self) -> Option<Phase> {
+        match self {
+            Phase::Draw => Some(Phase::Main),
+            Phase::Main => Some(Phase::Battle),
+            Phase::Battle => Some(Phase::End),
+            Phase::End => None,
+        }
+    }
+}
+
+// 当前回合 只在回合或阶段变化时修改 界面靠变化检测刷新
+#[derive(Resource, Debug, Clone)]
+pub struct TurnState {
+    pub turn: u32,
+    pub active_seat: usize,
+    pub phase: Phase,
+}
+
+impl Default for TurnState {
+    fn default() -> Self {
+        Self {
+            turn: 1,
+            active_seat: 0,
+            phase: Phase::default(),
+        }
+    }
+}
+
+impl TurnState {
+    pub fn is_active(&self, player: &Player) -> bool {
+        self.active_seat == player.seat
+    }
+
+    // 进入下一个阶段 结束阶段之后换人
+    pub fn advance_phase(&mut self, players: usize) {
+        match self.phase.next() {
+            Some(phase) => self.phase = phase,
+            None => self.end_turn(players),
+        }
+    }
+
+    pub fn end_turn(&mut self, players: usize) {
+        self.turn += 1;
+        self.active_seat = (self.active_seat + 1) % players.max(1);
+        self.phase = Phase::Draw;
+    }
+}
+
+// 请求结束回合 结束回合按钮和快捷键都从这里进入
+#[derive(Event, Debug, Clone, Copy)]
+pub struct EndTurnRequested;
+
+// 新回合从抽牌阶段开始 轮到的玩家抽一张
+pub fn end_turn_system(
+    mut commands: Commands,
+    mut requests: EventReader<EndTurnRequested>,
+    mut turn: ResMut<TurnState>,
+    mut players: Query<(Entity, &Player, &mut ZoneCounts)>,
+) {
+    // 同一帧多次请求只结束一次
+    if requests.read().count() == 0 {
+        return;
+    }
+    turn.end_turn(players.iter().count());
+    let mut entry = LogEntry::new(LogKind::Turn, "log.turn_start").with_arg("turn", turn.turn);
+    let active = players
+        .iter_mut()
+        .find(|(_, player, _)| turn.is_active(player));
+    if let Some((player, _, mut counts)) = active {
+        entry = entry.with_player(player);
+        commands.log_event(entry);
+        if turn.phase == Phase::Draw && counts.deck > 0 {
+            counts.deck -= 1;
+            counts.hand += 1;
+            commands.log_event(LogEntry::draw(player, 1));
+        }
+        return;
+    }
+    commands.log_event(entry);
+}
+
+// 卡片位置变化时重新统计手牌数 数量不变时不写入 避免触发变化检测
+// 没有卡片实体的玩家 (比如对手) 由规则层直接设置
+#[allow(clippy::type_complexity)]
+pub fn sync_hand_counts(
+    changed: Query<(), Or<(Changed<CardLocation>, Changed<Owner>)>>,
+    mut removed: RemovedComponents<CardLocation>,
+    cards: Query<(&CardLocation, &Owner)>,
+    mut players: Query<(Entity, &mut ZoneCounts), With<Player>>,
+) {
+    if changed.is_empty() && removed.read().count() == 0 {
+        return;
+    }
+    for (player, mut counts) in players.iter_mut() {
+        let mut owned = cards
+            .iter()
+            .filter(|(_, owner)| owner.0 == player)
+            .peekable();
+        if owned.peek().is_none() {
+            continue;
+        }
+        let hand = owned
+            .filter(|(location, _)| **location == CardLocation::Hand)
+            .count() as u32;
+        if counts.hand != hand {
+            counts.hand = hand;
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use crate::actions::{ActionState, InputAction};
+use crate::dialog::DialogStack;
+use crate::game::{EndTurnRequested, Life, Player, Resources, TurnState, ZoneCounts};
+use crate::locale::{Localization, LocalizedFont, LocalizedText};
+use crate::presentation::PresentationQueue;
+use crate::prompt::ChoicePrompt;
+use crate::theme::{
+    ButtonRole, FontRole, PanelRole, TextRole, ThemedButton, ThemedButtonText, ThemedPanel,
+    ThemedText, UiTheme,
+};
+use bevy::prelude::*;
+
+// 每个玩家一块面板 记录对应的玩家
+#[derive(Component, Debug)]
+pub struct HudPanel {
+    pub player: Entity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudField {
+    Name,
+    Life,
+    Lx,
+    JQ,
+    Deck,
+    Hand,
+    Graveyard,
+}
+
+#[derive(Component, Debug)]
+pub struct HudText {
+    pub player: Entity,
+    pub field: HudField,
+}
+
+#[derive(Component, Debug)]
+pub struct TurnIndicator;
+
+#[derive(Component, Debug)]
+pub struct EndTurnButton;
+
+fn field_text(
+    field: HudField,
+    player: &Player,
+    life: &Life,
+    resources: &Resources,
+    counts: &ZoneCounts,
+    localization: &Localization,
+) -> String {
+    let (key, value) = match field {
+        HudField::Name => return localization.get(&player.name).to_string(),
+        HudField::Life => ("hud.life", life.0.to_string()),
+        HudField::Lx => ("hud.lx", resources.lx.to_string()),
+        HudField::JQ => ("hud.jq", resources.jq.to_string()),
+        HudField::Deck => ("hud.deck", counts.deck.to_string()),
+        HudField::Hand => ("hud.hand", counts.hand.to_string()),
+        HudField::Graveyard => ("hud.graveyard", counts.graveyard.to_string()),
+    };
+    localization.format(key, &[("value", value)])
+}
+
+fn turn_text(turn: &TurnState, players: &Query<&Player>, localization: &Localization) -> String {
+    let name = players
+        .iter()
+        .find(|player| turn.is_active(player))
+        .map_or(String::new(), |player| player.name.clone());
+    localization.format(
+        "hud.turn",
+        &[
+            ("turn", turn.turn.to_string()),
+            ("player", name),
+            ("phase", turn.phase.key().to_string()),
+        ],
+    )
+}
+
+// 回合指示和结束回合按钮 只生成一次
+pub fn setup_hud(mut commands: Commands, localization: Res<Localization>, theme: Res<UiTheme>) {
+    let font = localization.font();
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(theme.spacing.large),
+                bottom: Val::Px(theme.spacing.large),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(theme.spacing.medium)),
+                row_gap: Val::Px(theme.spacing.medium),
+                ..default()
+            },
+            ThemedPanel(PanelRole::Panel),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TurnIndicator,
+                LocalizedFont,
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    ..default()
+                },
+                ThemedText::new(FontRole::Small, TextRole::Light),
+            ));
+            parent
+                .spawn((
+                    Button,
+                    EndTurnButton,
+                    ThemedButton::new(ButtonRole::Primary),
+                    Node {
+                        width: Val::Px(140.0),
+                        height: Val::Px(44.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(localization.get("hud.end_turn")),
+                        LocalizedText::new("hud.end_turn"),
+                        TextFont {
+                            font: font.clone(),
+                            ..default()
+                        },
+                        ThemedButtonText(FontRole::Body),
+                    ));
+                })
+                .observe(on_end_turn_click);
+        });
+}
+
+// 演出 对话框 选择进行中都不能结束回合
+fn on_end_turn_click(
+    _click: Trigger<Pointer<Click>>,
+    presentation: Res<PresentationQueue>,
+    dialogs: Res<DialogStack>,
+    prompts: Query<(), With<ChoicePrompt>>,
+    mut requests: EventWriter<EndTurnRequested>,
+) {
+    if presentation.is_blocking() || dialogs.is_open() || !prompts.is_empty() {
+        return;
+    }
+    requests.send(EndTurnRequested);
+}
+
+// 按键结束回合 和按钮一样的限制
+pub fn end_turn_action_system(
+    actions: Res<ActionState>,
+    presentation: Res<PresentationQueue>,
+    dialogs: Res<DialogStack>,
+    prompts: Query<(), With<ChoicePrompt>>,
+    mut requests: EventWriter<EndTurnRequested>,
+) {
+    if !actions.just_pressed(InputAction::EndTurn) {
+        return;
+    }
+    if presentation.is_blocking() || dialogs.is_open() || !prompts.is_empty() {
+        return;
+    }
+    requests.send(EndTurnRequested);
+}
+
+// 新加入的玩家生成面板 第一个座位在左下 其他依次往上排
+pub fn spawn_player_panels(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    theme: Res<UiTheme>,
+    players: Query<(Entity, &Player, &Life, &Resources, &ZoneCounts), Added<Player>>,
+) {
+    for (entity, player, life, resources, counts) in players.iter() {
+        let font = localization.font();
+        let mut node = Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(theme.spacing.large),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(theme.spacing.medium)),
+            row_gap: Val::Px(theme.spacing.small),
+            ..default()
+        };
+        if player.seat == 0 {
+            node.bottom = Val::Px(theme.spacing.large);
+        } else {
+            node.top = Val::Px(theme.spacing.large + 180.0 * (player.seat - 1) as f32);
+        }
+        commands
+            .spawn((
+                HudPanel { player: entity },
+                node,
+                ThemedPanel(PanelRole::Panel),
+            ))
+            .with_children(|parent| {
+                for (field, size) in [
+                    (HudField::Name, FontRole::Heading),
+                    (HudField::Life, FontRole::Body),
+                    (HudField::Lx, FontRole::Small),
+                    (HudField::JQ, FontRole::Small),
+                    (HudField::Deck, FontRole::Small),
+                    (HudField::Hand, FontRole::Small),
+                    (HudField::Graveyard, FontRole::Small),
+                ] {
+                    parent.spawn((
+                        HudText {
+                            player: entity,
+                            field,
+                        },
+                        LocalizedFont,
+                        Text::new(field_text(
+                            field,
+                            player,
+                            life,
+                            resources,
+                            counts,
+                            &localization,
+                        )),
+                        TextFont {
+                            font: font.clone(),
+                            ..default()
+                        },
+                        ThemedText::new(size, TextRole::Light),
+                    ));
+                }
+            });
+    }
+}
+
+// 只刷新数值变化的玩家 切换语言时全部刷新
+#[allow(clippy::type_complexity)]
+pub fn update_player_hud(
+    localization: Res<Localization>,
+    changed: Query<
+        Entity,
+        (
+            With<Player>,
+            Or<(
+                Changed<Player>,
+                Changed<Life>,
+                Changed<Resources>,
+                Changed<ZoneCounts>,
+            )>,
+        ),
+    >,
+    players: Query<(&Player, &Life, &Resources, &ZoneCounts)>,
+    mut texts: Query<(&HudText, &mut Text)>,
+) {
+    let all = localization.is_changed();
+    if changed.is_empty() && !all {
+        return;
+    }
+    for (hud, mut text) in texts.iter_mut() {
+        if !all && !changed.contains(hud.player) {
+            continue;
+        }
+        let Ok((player, life, resources, counts)) = players.get(hud.player) else {
+            continue;
+        };
+        let value = field_text(hud.field, player, life, resources, counts, &localization);
+        if text.0 != value {
+            text.0 = value;
+        }
+    }
+}
+
+// 回合或阶段变化时刷新指示 同时高亮当前玩家
+pub fn update_turn_hud(
+    localization: Res<Localization>,
+    turn: Res<TurnState>,
+    players: Query<&Player>,
+    added: Query<(), Added<HudPanel>>,
+    mut indicators: Query<&mut Text, With<TurnIndicator>>,
+    mut panels: Query<(&HudPanel, &mut ThemedPanel)>,
+) {
+    if !turn.is_changed() && !localization.is_changed() && added.is_empty() {
+        return;
+    }
+    for mut text in indicators.iter_mut() {
+        text.0 = turn_text(&turn, &players, &localization);
+    }
+    for (panel, mut themed) in panels.iter_mut() {
+        let active = players
+            .get(panel.player)
+            .is_ok_and(|player| turn.is_active(player));
+        themed.set_if_neq(ThemedPanel(if active {
+            PanelRole::Active
+        } else {
+            PanelRole::Panel
+        }));
+    }
+}
+
+// 不能结束回合时按钮变灰 状态不变时不写入
+pub fn end_turn_button_state(
+    presentation: Res<PresentationQueue>,
+    dialogs: Res<DialogStack>,
+    prompts: Query<(), With<ChoicePrompt>>,
+    mut buttons: Query<&mut ThemedButton, With<EndTurnButton>>,
+) {
+    let disabled = presentation.is_blocking() || dialogs.is_open() || !prompts.is_empty();
+    for mut button in buttons.iter_mut() {
+        if button.disabled != disabled {
+            button.disabled = disabled;
+        }
+    }
+}
+
+// 玩家移除时一起移除面板
+pub fn despawn_player_panels(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Player>,
+    panels: Query<(Entity, &HudPanel)>,
+) {
+    for player in removed.read() {
+        for (entity, panel) in panels.iter() {
+            if panel.player == player {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_hud).add_systems(
+            Update,
+            (
+                spawn_player_panels,
+                despawn_player_panels,
+                update_player_hud,
+                update_turn_hud,
+                end_turn_action_system,
+                end_turn_button_state,
+            )
+                .chain(),
+        );
+    }
+}
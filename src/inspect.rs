@@ -0,0 +1,187 @@
+use crate::actions::{ActionState, InputAction};
+use crate::cards::{Card, CardInfo, CardText};
+use crate::focus::FocusCursor;
+use crate::locale::{Localization, LocalizedFont};
+use crate::theme::{FontRole, PanelRole, TextRole, ThemedPanel, ThemedText, UiTheme};
+use bevy::prelude::*;
+use std::time::Duration;
+
+// 悬停多久之后才显示大图
+const PREVIEW_DELAY: f32 = 0.6;
+
+// 悬停查看 记录当前悬停的卡片和右侧的大图
+#[derive(Resource)]
+pub struct HoverInspect {
+    pub delay: Timer,
+    card: Option<Entity>,
+    preview: Option<Entity>,
+}
+
+impl Default for HoverInspect {
+    fn default() -> Self {
+        Self {
+            delay: Timer::from_seconds(PREVIEW_DELAY, TimerMode::Once),
+            card: None,
+            preview: None,
+        }
+    }
+}
+
+impl HoverInspect {
+    pub fn begin(&mut self, card: Entity) {
+        if self.card != Some(card) {
+            self.card = Some(card);
+            self.delay.reset();
+        }
+    }
+
+    // 只结束指定卡片的悬停 避免 Over/Out 先后顺序打乱
+    pub fn end(&mut self, card: Entity) {
+        if self.card == Some(card) {
+            self.card = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.card = None;
+    }
+
+    pub fn card(&self) -> Option<Entity> {
+        self.card
+    }
+
+    // 不等延迟直接显示大图
+    pub fn show_now(&mut self, card: Entity) {
+        self.begin(card);
+        let duration = self.delay.duration();
+        self.delay.set_elapsed(duration);
+    }
+}
+
+// 查看键 显示焦点或悬停的卡片的大图 再按一次收起
+pub fn inspect_action_system(
+    actions: Res<ActionState>,
+    focus: Option<Res<FocusCursor>>,
+    cards: Query<(), With<Card>>,
+    mut inspect: ResMut<HoverInspect>,
+) {
+    if !actions.just_pressed(InputAction::InspectCard) {
+        return;
+    }
+    let focused = focus
+        .filter(|focus| focus.is_active())
+        .and_then(|focus| focus.target)
+        .filter(|target| cards.contains(*target));
+    let Some(card) = focused.or(inspect.card) else {
+        return;
+    };
+    if inspect.card == Some(card) && inspect.preview.is_some() {
+        inspect.clear();
+    } else {
+        inspect.show_now(card);
+    }
+}
+
+#[derive(Component)]
+pub struct InspectPreview {
+    pub card: Entity,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn hover_preview_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    localization: Res<Localization>,
+    theme: Res<UiTheme>,
+    mut inspect: ResMut<HoverInspect>,
+    cards: Query<(&Children, Option<&CardText>)>,
+    card_infos: Query<&CardInfo>,
+    previews: Query<&InspectPreview>,
+) {
+    // 悬停的卡片变了 先移除旧的大图
+    if let Some(preview) = inspect.preview {
+        let stale = previews
+            .get(preview)
+            .map_or(true, |p| Some(p.card) != inspect.card);
+        if stale {
+            if let Some(entity) = commands.get_entity(preview) {
+                entity.despawn_recursive();
+            }
+            inspect.preview = None;
+        }
+    }
+
+    let Some(card) = inspect.card else {
+        return;
+    };
+    if inspect.preview.is_some() {
+        return;
+    }
+    inspect
+        .delay
+        .tick(Duration::from_secs_f32(time.delta_secs()));
+    if !inspect.delay.finished() {
+        return;
+    }
+    let Ok((children, text)) = cards.get(card) else {
+        return;
+    };
+    let Some(info) = card_infos.iter_many(children).next() else {
+        return;
+    };
+
+    let font = localization.font();
+    let preview = commands
+        .spawn((
+            InspectPreview { card },
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(theme.spacing.large),
+                top: Val::Px(theme.spacing.large),
+                width: Val::Px(360.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(theme.spacing.medium)),
+                row_gap: Val::Px(theme.spacing.medium),
+                ..default()
+            },
+            ThemedPanel(PanelRole::Panel),
+            // 大图不能挡住下面的卡片 否则会触发 Out
+            PickingBehavior::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageNode::new(info.image.clone()),
+                Node {
+                    width: Val::Px(340.0),
+                    height: Val::Px(476.0),
+                    ..default()
+                },
+                PickingBehavior::IGNORE,
+            ));
+            if let Some(text) = text {
+                parent.spawn((
+                    Text::new(text.name.clone()),
+                    LocalizedFont,
+                    TextFont {
+                        font: font.clone(),
+                        ..default()
+                    },
+                    ThemedText::new(FontRole::Heading, TextRole::Light),
+                    PickingBehavior::IGNORE,
+                ));
+                parent.spawn((
+                    Text::new(text.rules.clone()),
+                    LocalizedFont,
+                    TextFont {
+                        font: font.clone(),
+                        ..default()
+                    },
+                    ThemedText::new(FontRole::Small, TextRole::Muted),
+                    PickingBehavior::IGNORE,
+                ));
+            }
+        })
+        .id();
+    inspect.preview = Some(preview);
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use bevy_tween::bevy_time_runner::{TimeRunnerEnded, TimeSpan};
+use bevy_tween::combinator::AnimationBuilder;
+use bevy_tween::prelude::AnimationBuilderExt;
+
+// 动画完整播放结束 target 默认是播放动画的实体
+// 同时以 target 为目标触发 可以用 observe 监听
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AnimationFinished {
+    pub time_runner: Entity,
+    pub target: Entity,
+}
+
+type FinishCallback = Box<dyn FnOnce(&mut Commands, Entity) + Send + Sync>;
+type FollowUp = Box<dyn FnOnce(AnimationBuilder) + Send + Sync>;
+
+enum FinishHook {
+    // 删除已经结束的 tween 子实体
+    ClearTweens,
+    Notify,
+    Callback(FinishCallback),
+    // 在同一个实体上接着播放下一段动画
+    FollowUp(FollowUp),
+    DespawnRunner,
+    DespawnTarget,
+}
+
+// 挂在 TimeRunner 所在实体上 结束时按添加顺序执行
+#[derive(Component, Default)]
+pub struct AnimationLifecycle {
+    target: Option<Entity>,
+    hooks: Vec<FinishHook>,
+}
+
+pub trait AnimationLifecycleExt {
+    // 动画实际作用的实体 不设置时就是动画实体本身
+    fn with_target(self, target: Entity) -> Self;
+    fn clear_on_finish(self) -> Self;
+    // 发出 AnimationFinished
+    fn notify_on_complete(self) -> Self;
+    fn on_complete(
+        self,
+        callback: impl FnOnce(&mut Commands, Entity) + Send + Sync + 'static,
+    ) -> Self;
+    fn insert_on_finish(self, bundle: impl Bundle) -> Self;
+    fn remove_on_finish<T: Bundle>(self) -> Self;
+    fn then(self, follow_up: impl FnOnce(AnimationBuilder) + Send + Sync + 'static) -> Self;
+    // 删除动画实体 用于 commands.animation() 单独生成的动画
+    fn despawn_on_finish(self) -> Self;
+    fn despawn_target_on_finish(self) -> Self;
+}
+
+impl AnimationLifecycleExt for AnimationBuilder<'_> {
+    fn with_target(mut self, target: Entity) -> Self {
+        self.entity_commands()
+            .entry::<AnimationLifecycle>()
+            .or_default()
+            .and_modify(move |mut lifecycle| lifecycle.target = Some(target));
+        self
+    }
+
+    fn clear_on_finish(self) -> Self {
+        push_hook(self, FinishHook::ClearTweens)
+    }
+
+    fn notify_on_complete(self) -> Self {
+        push_hook(self, FinishHook::Notify)
+    }
+
+    fn on_complete(
+        self,
+        callback: impl FnOnce(&mut Commands, Entity) + Send + Sync + 'static,
+    ) -> Self {
+        push_hook(self, FinishHook::Callback(Box::new(callback)))
+    }
+
+    fn insert_on_finish(self, bundle: impl Bundle) -> Self {
+        self.on_complete(move |commands, target| {
+            if let Some(mut entity) = commands.get_entity(target) {
+                entity.insert(bundle);
+            }
+        })
+    }
+
+    fn remove_on_finish<T: Bundle>(self) -> Self {
+        self.on_complete(move |commands, target| {
+            if let Some(mut entity) = commands.get_entity(target) {
+                entity.remove::<T>();
+            }
+        })
+    }
+
+    fn then(self, follow_up: impl FnOnce(AnimationBuilder) + Send + Sync + 'static) -> Self {
+        push_hook(self, FinishHook::FollowUp(Box::new(follow_up)))
+    }
+
+    fn despawn_on_finish(self) -> Self {
+        push_hook(self, FinishHook::DespawnRunner)
+    }
+
+    fn despawn_target_on_finish(self) -> Self {
+        push_hook(self, FinishHook::DespawnTarget)
+    }
+}
+
+fn push_hook(mut builder: AnimationBuilder<'_>, hook: FinishHook) -> AnimationBuilder<'_> {
+    builder
+        .entity_commands()
+        .entry::<AnimationLifecycle>()
+        .or_default()
+        .and_modify(move |mut lifecycle| lifecycle.hooks.push(hook));
+    builder
+}
+
+pub fn animation_lifecycle_system(
+    mut commands: Commands,
+    mut time_runner_ended: EventReader<TimeRunnerEnded>,
+    mut lifecycles: Query<&mut AnimationLifecycle>,
+    q_children: Query<&Children>,
+    q_tween: Query<(Entity, Has<TimeSpan>)>,
+    mut finished: EventWriter<AnimationFinished>,
+) {
+    for ended in time_runner_ended.read() {
+        if !ended.is_completed() {
+            continue;
+        }
+        let runner = ended.time_runner;
+        let Ok(mut lifecycle) = lifecycles.get_mut(runner) else {
+            continue;
+        };
+        let target = lifecycle.target.unwrap_or(runner);
+        let hooks = std::mem::take(&mut lifecycle.hooks);
+        commands.entity(runner).remove::<AnimationLifecycle>();
+
+        for hook in hooks {
+            match hook {
+                FinishHook::ClearTweens => {
+                    clear_tweens(&mut commands, runner, &q_children, &q_tween);
+                }
+                FinishHook::Notify => {
+                    let event = AnimationFinished {
+                        time_runner: runner,
+                        target,
+                    };
+                    commands.trigger_targets(event, target);
+                    finished.send(event);
+                }
+                FinishHook::Callback(callback) => callback(&mut commands, target),
+                FinishHook::FollowUp(follow_up) => {
+                    clear_tweens(&mut commands, runner, &q_children, &q_tween);
+                    if let Some(mut entity) = commands.get_entity(runner) {
+                        follow_up(entity.animation());
+                    }
+                }
+                FinishHook::DespawnRunner => {
+                    if let Some(entity) = commands.get_entity(runner) {
+                        entity.despawn_recursive();
+                    }
+                }
+                FinishHook::DespawnTarget => {
+                    if let Some(entity) = commands.get_entity(target) {
+                        entity.despawn_recursive();
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn clear_tweens(
+    commands: &mut Commands,
+    runner: Entity,
+    q_children: &Query<&Children>,
+    q_tween: &Query<(Entity, Has<TimeSpan>)>,
+) {
+    let Ok(children) = q_children.get(runner) else {
+        return;
+    };
+    for (entity, is_tween) in q_tween.iter_many(children) {
+        if is_tween {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,624 @@
+use crate::board::BoardConfig;
+use crate::cards::CardsConfig;
+use crate::effects::EffectsConfig;
+use crate::locale::{LocaleFile, Localization, LocalizedText};
+use crate::theme::{
+    ButtonRole, FontRole, PanelRole, TextRole, ThemedButton, ThemedButtonText, ThemedPanel,
+    ThemedText, UiTheme,
+};
+use bevy::asset::{LoadState, RecursiveDependencyLoadState};
+use bevy::prelude::*;
+
+// 对局之前先加载资源 全部完成后才进入对局
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MatchState {
+    #[default]
+    Loading,
+    InMatch,
+}
+
+// 预加载的资源分类 加载失败时显示在路径前面
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Card,
+    Texture,
+    Font,
+    Board,
+    Locale,
+}
+
+impl AssetKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            AssetKind::Card => "loading.kind.card",
+            AssetKind::Texture => "loading.kind.texture",
+            AssetKind::Font => "loading.kind.font",
+            AssetKind::Board => "loading.kind.board",
+            AssetKind::Locale => "loading.kind.locale",
+        }
+    }
+}
+
+// 程序自己要用的资源 卡片和场地 卡面 特效的素材从各自的配置里取
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AssetManifest {
+    // 卡片编号 卡图是 <编号>.png 名字和规则在语言文件里
+    pub cards: Vec<String>,
+    pub textures: Vec<String>,
+    pub fonts: Vec<String>,
+}
+
+impl AssetManifest {
+    pub fn with_card(mut self, id: impl Into<String>) -> Self {
+        self.cards.push(id.into());
+        self
+    }
+
+    pub fn with_texture(mut self, path: impl Into<String>) -> Self {
+        self.textures.push(path.into());
+        self
+    }
+
+    pub fn with_font(mut self, path: impl Into<String>) -> Self {
+        self.fonts.push(path.into());
+        self
+    }
+
+    pub fn card_image_path(id: &str) -> String {
+        format!("{}.png", id)
+    }
+}
+
+#[derive(Debug)]
+struct PreloadEntry {
+    kind: AssetKind,
+    path: String,
+    handle: UntypedHandle,
+    // 缺了也能用 失败只记警告
+    optional: bool,
+    done: bool,
+    error: Option<String>,
+}
+
+// 预加载中的资源 加载完后继续持有句柄 进入对局时不会被释放
+#[derive(Resource, Debug, Default)]
+pub struct Preloaded {
+    entries: Vec<PreloadEntry>,
+}
+
+impl Preloaded {
+    pub fn add<A: Asset>(
+        &mut self,
+        asset_server: &AssetServer,
+        kind: AssetKind,
+        path: impl Into<String>,
+    ) {
+        self.push::<A>(asset_server, kind, path.into(), false);
+    }
+
+    // 语言文件里的备用字体 缺了就用默认字体 不算加载失败
+    pub fn add_optional<A: Asset>(
+        &mut self,
+        asset_server: &AssetServer,
+        kind: AssetKind,
+        path: impl Into<String>,
+    ) {
+        self.push::<A>(asset_server, kind, path.into(), true);
+    }
+
+    // 同一个路径只加载一次
+    fn push<A: Asset>(
+        &mut self,
+        asset_server: &AssetServer,
+        kind: AssetKind,
+        path: String,
+        optional: bool,
+    ) {
+        if self.entries.iter().any(|entry| entry.path == path) {
+            return;
+        }
+        let handle = asset_server.load::<A>(path.clone()).untyped();
+        self.entries.push(PreloadEntry {
+            kind,
+            path,
+            handle,
+            optional,
+            done: false,
+            error: None,
+        });
+    }
+
+    pub fn total(&self) -> usize {
+        self.entries.len()
+    }
+
+    // 加载完成和失败的都算处理完
+    pub fn finished(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.done).count()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.entries.iter().all(|entry| entry.done)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = (AssetKind, &str, &str)> {
+        self.entries.iter().filter_map(|entry| {
+            entry
+                .error
+                .as_deref()
+                .map(|error| (entry.kind, entry.path.as_str(), error))
+        })
+    }
+}
+
+// 按清单和各个插件的配置开始加载
+pub fn start_preload(
+    asset_server: Res<AssetServer>,
+    manifest: Res<AssetManifest>,
+    localization: Res<Localization>,
+    cards_config: Option<Res<CardsConfig>>,
+    board: Option<Res<BoardConfig>>,
+    effects: Option<Res<EffectsConfig>>,
+    mut preloaded: ResMut<Preloaded>,
+) {
+    for id in &manifest.cards {
+        let path = AssetManifest::card_image_path(id);
+        preloaded.add::<Image>(&asset_server, AssetKind::Card, path);
+    }
+    if let Some(config) = cards_config {
+        for path in [
+            &config.cost_gem,
+            &config.stat_left,
+            &config.stat_right,
+            &config.default_back,
+        ] {
+            preloaded.add::<Image>(&asset_server, AssetKind::Texture, path.clone());
+        }
+    }
+    for path in &manifest.textures {
+        preloaded.add::<Image>(&asset_server, AssetKind::Texture, path.clone());
+    }
+    if let Some(config) = effects {
+        preloaded.add::<Image>(
+            &asset_server,
+            AssetKind::Texture,
+            config.explosion_image.clone(),
+        );
+    }
+    if let Some(board) = board {
+        for path in board.images.paths() {
+            preloaded.add::<Image>(&asset_server, AssetKind::Board, path.to_string());
+        }
+    }
+    for path in &manifest.fonts {
+        preloaded.add::<Font>(&asset_server, AssetKind::Font, path.clone());
+    }
+    for language in localization.languages() {
+        let path = format!("locales/{}.lang", language);
+        preloaded.add::<LocaleFile>(&asset_server, AssetKind::Locale, path);
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct LoadingScreen;
+
+#[derive(Component, Debug)]
+pub struct LoadingBarFill;
+
+#[derive(Component, Debug)]
+pub struct LoadingProgressText;
+
+#[derive(Component, Debug)]
+pub struct LoadingFailureList;
+
+#[derive(Component, Debug)]
+pub struct LoadingContinueButton;
+
+// 盖住整个画面 对局的相机还没生成 自带一个界面相机
+pub fn setup_loading_screen(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    theme: Res<UiTheme>,
+) {
+    let font = localization.font();
+    commands.spawn((LoadingScreen, Camera2d));
+    commands
+        .spawn((
+            LoadingScreen,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(theme.spacing.medium),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+            GlobalZIndex(200),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(localization.get("loading.title")),
+                LocalizedText::new("loading.title"),
+                TextFont {
+                    font: font.clone(),
+                    ..default()
+                },
+                ThemedText::new(FontRole::Title, TextRole::Light),
+            ));
+            // 进度条
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(420.0),
+                        height: Val::Px(18.0),
+                        padding: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    ThemedPanel(PanelRole::Dialog),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        LoadingBarFill,
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(theme.palette.accent),
+                    ));
+                });
+            parent.spawn((
+                LoadingProgressText,
+                Text::new(""),
+                LocalizedText::new("loading.progress")
+                    .with_arg("done", 0)
+                    .with_arg("total", 0),
+                TextFont {
+                    font: font.clone(),
+                    ..default()
+                },
+                ThemedText::new(FontRole::Small, TextRole::Muted),
+            ));
+            parent.spawn((
+                LoadingFailureList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    max_width: Val::Percent(80.0),
+                    row_gap: Val::Px(theme.spacing.small),
+                    ..default()
+                },
+            ));
+            // 有资源失败时才显示 由玩家决定要不要继续
+            parent
+                .spawn((
+                    Button,
+                    LoadingContinueButton,
+                    ThemedButton::new(ButtonRole::Primary),
+                    Node {
+                        display: Display::None,
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                        ..default()
+                    },
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(localization.get("loading.continue")),
+                        LocalizedText::new("loading.continue"),
+                        TextFont {
+                            font: font.clone(),
+                            ..default()
+                        },
+                        ThemedButtonText(FontRole::Body),
+                        PickingBehavior::IGNORE,
+                    ));
+                })
+                .observe(on_continue_click);
+        });
+}
+
+// 检查每个资源的状态 更新进度条 失败的列出路径
+// 语言文件加载后把它用到的字体也加进来
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn preload_progress_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    locale_files: Res<Assets<LocaleFile>>,
+    localization: Res<Localization>,
+    mut preloaded: ResMut<Preloaded>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut fills: Query<&mut Node, (With<LoadingBarFill>, Without<LoadingContinueButton>)>,
+    mut texts: Query<&mut LocalizedText, With<LoadingProgressText>>,
+    lists: Query<Entity, With<LoadingFailureList>>,
+    mut buttons: Query<&mut Node, (With<LoadingContinueButton>, Without<LoadingBarFill>)>,
+) {
+    let font = localization.font();
+    for entry in preloaded.entries.iter_mut().filter(|entry| !entry.done) {
+        // 自己加载好了但依赖失败时也算失败 不然会一直卡在加载中
+        let error = match asset_server.load_state(entry.handle.id()) {
+            LoadState::Loaded => {
+                match asset_server.recursive_dependency_load_state(entry.handle.id()) {
+                    RecursiveDependencyLoadState::Loaded => {
+                        entry.done = true;
+                        continue;
+                    }
+                    RecursiveDependencyLoadState::Failed(error) => error,
+                    _ => continue,
+                }
+            }
+            LoadState::Failed(error) => error,
+            _ => continue,
+        };
+        entry.done = true;
+        if entry.optional {
+            warn!("可选资源加载失败 跳过 {}: {}", entry.path, error);
+            continue;
+        }
+        warn!("资源加载失败 {}: {}", entry.path, error);
+        entry.error = Some(error.to_string());
+        for list in lists.iter() {
+            commands.entity(list).with_child((
+                Text::new(""),
+                LocalizedText::new("loading.failed")
+                    .with_arg("kind", entry.kind.key())
+                    .with_arg("path", entry.path.clone()),
+                TextFont {
+                    font: font.clone(),
+                    ..default()
+                },
+                ThemedText::new(FontRole::Small, TextRole::Error),
+            ));
+        }
+    }
+    // 放在检查之后 同一帧加载完的语言文件的字体也算进总数
+    let fonts: Vec<String> = locale_files
+        .iter()
+        .flat_map(|(_, file)| file.fonts.iter().cloned())
+        .collect();
+    for path in fonts {
+        preloaded.add_optional::<Font>(&asset_server, AssetKind::Font, path);
+    }
+
+    let (finished, total) = (preloaded.finished(), preloaded.total());
+    let percent = if total == 0 {
+        100.0
+    } else {
+        finished as f32 / total as f32 * 100.0
+    };
+    for mut fill in fills.iter_mut() {
+        fill.width = Val::Percent(percent);
+    }
+    let args = [("done", finished.to_string()), ("total", total.to_string())];
+    for mut text in texts.iter_mut() {
+        // 数字变了才改 不然每帧都会刷新文本
+        if text.args.as_slice() != args.as_slice() {
+            text.args = args.to_vec();
+        }
+    }
+    if !preloaded.is_finished() {
+        return;
+    }
+    if preloaded.failures().next().is_none() {
+        next_state.set(MatchState::InMatch);
+        return;
+    }
+    for mut button in buttons.iter_mut() {
+        button.display = Display::Flex;
+    }
+}
+
+pub fn on_continue_click(
+    mut click: Trigger<Pointer<Click>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    click.propagate(false);
+    next_state.set(MatchState::InMatch);
+}
+
+pub fn cleanup_loading_screen(mut commands: Commands, screens: Query<Entity, With<LoadingScreen>>) {
+    for entity in screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// 资源预加载和加载界面 完成后切到 MatchState::InMatch
+#[derive(Default)]
+pub struct LoadingPlugin {
+    pub manifest: AssetManifest,
+}
+
+impl LoadingPlugin {
+    pub fn new(manifest: AssetManifest) -> Self {
+        Self { manifest }
+    }
+}
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<MatchState>()
+            .insert_resource(self.manifest.clone())
+            .init_resource::<Preloaded>()
+            .add_systems(
+                OnEnter(MatchState::Loading),
+                (start_preload, setup_loading_screen),
+            )
+            .add_systems(
+                Update,
+                preload_progress_system.run_if(in_state(MatchState::Loading)),
+            )
+            .add_systems(OnExit(MatchState::Loading), cleanup_loading_screen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::read_source;
+    use bevy::asset::io::memory::{Dir, MemoryAssetReader};
+    use bevy::asset::io::{AssetSource, Reader};
+    use bevy::asset::{AssetLoader, LoadContext};
+    use bevy::picking::backend::HitData;
+    use bevy::picking::pointer::{Location, PointerButton, PointerId};
+    use bevy::render::camera::NormalizedRenderTarget;
+    use bevy::state::app::StatesPlugin;
+    use std::path::Path;
+    use std::time::Duration;
+
+    // 每行是一个依赖的路径
+    #[derive(Asset, TypePath, Debug)]
+    struct TestAsset;
+
+    #[derive(Default)]
+    struct TestLoader;
+
+    impl AssetLoader for TestLoader {
+        type Asset = TestAsset;
+        type Settings = ();
+        type Error = std::io::Error;
+
+        async fn load(
+            &self,
+            reader: &mut dyn Reader,
+            _settings: &(),
+            load_context: &mut LoadContext<'_>,
+        ) -> Result<Self::Asset, Self::Error> {
+            for line in read_source(reader).await?.lines() {
+                load_context.load::<TestAsset>(line.to_string());
+            }
+            Ok(TestAsset)
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["test"]
+        }
+    }
+
+    fn app(paths: &[&str]) -> App {
+        let dir = Dir::default();
+        dir.insert_asset_text(Path::new("ok.test"), "");
+        dir.insert_asset_text(Path::new("broken.test"), "memory://gone.test");
+        let mut app = App::new();
+        app.register_asset_source(
+            "memory",
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        )
+        .add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+        .init_asset::<TestAsset>()
+        .init_asset::<LocaleFile>()
+        .init_asset_loader::<TestLoader>()
+        .init_resource::<Localization>()
+        .init_resource::<UiTheme>()
+        .init_resource::<Preloaded>()
+        .init_state::<MatchState>()
+        .add_systems(OnEnter(MatchState::Loading), setup_loading_screen)
+        .add_systems(
+            Update,
+            preload_progress_system.run_if(in_state(MatchState::Loading)),
+        )
+        .add_systems(OnExit(MatchState::Loading), cleanup_loading_screen);
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let mut preloaded = app.world_mut().resource_mut::<Preloaded>();
+        for path in paths {
+            preloaded.add::<TestAsset>(&asset_server, AssetKind::Texture, *path);
+        }
+        app
+    }
+
+    // 加载在别的线程 等到全部处理完
+    fn wait_for_preload(app: &mut App) {
+        for _ in 0..500 {
+            app.update();
+            if app.world().resource::<Preloaded>().is_finished() {
+                // 再跑一帧让状态切换生效
+                app.update();
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        panic!("预加载没有结束");
+    }
+
+    fn state(app: &App) -> MatchState {
+        *app.world().resource::<State<MatchState>>().get()
+    }
+
+    #[test]
+    fn all_loaded_enters_match() {
+        let mut app = app(&["memory://ok.test"]);
+        wait_for_preload(&mut app);
+        assert_eq!(app.world().resource::<Preloaded>().failures().count(), 0);
+        assert_eq!(state(&app), MatchState::InMatch);
+    }
+
+    #[test]
+    fn failures_wait_for_continue() {
+        let mut app = app(&[
+            "memory://ok.test",
+            "memory://missing.test",
+            "memory://broken.test",
+        ]);
+        wait_for_preload(&mut app);
+
+        // 找不到的文件和依赖坏掉的文件都算失败
+        let mut failed: Vec<String> = app
+            .world()
+            .resource::<Preloaded>()
+            .failures()
+            .map(|(_, path, _)| path.to_string())
+            .collect();
+        failed.sort();
+        assert_eq!(failed, ["memory://broken.test", "memory://missing.test"]);
+        // 失败列表里显示路径
+        let mut shown: Vec<String> = app
+            .world_mut()
+            .query::<&LocalizedText>()
+            .iter(app.world())
+            .filter(|text| text.key == "loading.failed")
+            .filter_map(|text| {
+                text.args
+                    .iter()
+                    .find(|(name, _)| *name == "path")
+                    .map(|(_, path)| path.clone())
+            })
+            .collect();
+        shown.sort();
+        assert_eq!(shown, failed);
+
+        // 没点继续之前一直停在加载界面
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(state(&app), MatchState::Loading);
+        let (button, node) = app
+            .world_mut()
+            .query_filtered::<(Entity, &Node), With<LoadingContinueButton>>()
+            .single(app.world());
+        assert_eq!(node.display, Display::Flex);
+
+        let location = Location {
+            target: NormalizedRenderTarget::Image(Handle::default()),
+            position: Vec2::ZERO,
+        };
+        let click = Click {
+            button: PointerButton::Primary,
+            hit: HitData::new(button, 0.0, None, None),
+            duration: Duration::ZERO,
+        };
+        app.world_mut().trigger_targets(
+            Pointer::new(button, PointerId::Mouse, location, click),
+            button,
+        );
+        app.update();
+        assert_eq!(state(&app), MatchState::InMatch);
+        // 进入对局后加载界面被清掉
+        assert!(app
+            .world_mut()
+            .query_filtered::<(), With<LoadingScreen>>()
+            .iter(app.world())
+            .next()
+            .is_none());
+    }
+}
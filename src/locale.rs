@@ -0,0 +1,368 @@
+use crate::cards::CardText;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoadFailedEvent, AssetLoader, LoadContext, LoadState};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+pub const DEFAULT_LANGUAGE: &str = "zh-CN";
+
+// 语言文件 assets/locales/<语言>.lang
+// 每行一个 key = value  # 开头是注释
+// @font 可以写多行 按顺序回退  @fallback 是缺少的 key 去哪个语言找
+#[derive(Asset, TypePath, Debug, Default)]
+pub struct LocaleFile {
+    pub name: Option<String>,
+    pub fonts: Vec<String>,
+    pub fallback: Option<String>,
+    pub entries: HashMap<String, String>,
+}
+
+// key = value 格式的文本 语言文件和主题文件共用
+pub fn parse_key_values(source: &str) -> Result<Vec<(String, String)>, std::io::Error> {
+    let mut pairs = vec![];
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("line {}: expected `key = value`", number + 1),
+            ));
+        };
+        pairs.push((key.trim().to_string(), value.trim().replace("\\n", "\n")));
+    }
+    Ok(pairs)
+}
+
+pub(crate) async fn read_source(reader: &mut dyn Reader) -> Result<String, std::io::Error> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    String::from_utf8(bytes)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+impl LocaleFile {
+    pub fn parse(source: &str) -> Result<Self, std::io::Error> {
+        let mut file = LocaleFile::default();
+        for (key, value) in parse_key_values(source)? {
+            match key.as_str() {
+                "@name" => file.name = Some(value),
+                "@font" => file.fonts.push(value),
+                "@fallback" => file.fallback = Some(value),
+                _ => {
+                    file.entries.insert(key, value);
+                }
+            }
+        }
+        Ok(file)
+    }
+}
+
+#[derive(Default)]
+pub struct LocaleLoader;
+
+impl AssetLoader for LocaleLoader {
+    type Asset = LocaleFile;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        LocaleFile::parse(&read_source(reader).await?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang"]
+    }
+}
+
+// 当前语言的文本表和字体 切换语言或者语言文件重新加载后整体重建
+// 界面靠它的变化检测刷新
+#[derive(Resource)]
+pub struct Localization {
+    language: String,
+    default_language: String,
+    languages: Vec<String>,
+    files: HashMap<String, Handle<LocaleFile>>,
+    entries: HashMap<String, String>,
+    fonts: Vec<Handle<Font>>,
+    font: Handle<Font>,
+    dirty: bool,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self::new(DEFAULT_LANGUAGE, &[DEFAULT_LANGUAGE, "en"])
+    }
+}
+
+impl Localization {
+    pub fn new(default_language: &str, languages: &[&str]) -> Self {
+        Self {
+            language: default_language.to_string(),
+            default_language: default_language.to_string(),
+            languages: languages.iter().map(|l| l.to_string()).collect(),
+            files: HashMap::new(),
+            entries: HashMap::new(),
+            fonts: vec![],
+            font: Handle::default(),
+            dirty: true,
+        }
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    pub fn set_language(&mut self, language: impl Into<String>) {
+        let language = language.into();
+        if self.language != language {
+            self.language = language;
+            self.dirty = true;
+        }
+    }
+
+    // 按顺序切到下一个语言
+    pub fn cycle_language(&mut self) {
+        let index = self
+            .languages
+            .iter()
+            .position(|l| *l == self.language)
+            .map_or(0, |i| (i + 1) % self.languages.len().max(1));
+        if let Some(language) = self.languages.get(index).cloned() {
+            self.set_language(language);
+        }
+    }
+
+    // 找不到时返回 key 本身 没有翻译的文本也可以直接传进来
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.entries.get(key).map_or(key, |value| value.as_str())
+    }
+
+    // 替换 {name} 形式的参数 参数本身也会先翻译
+    pub fn format(&self, key: &str, args: &[(&str, String)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), self.get(value));
+        }
+        text
+    }
+
+    pub fn font(&self) -> Handle<Font> {
+        self.font.clone()
+    }
+
+    // 当前语言 -> 它的 @fallback -> 默认语言
+    fn chain(&self, files: &Assets<LocaleFile>) -> Vec<String> {
+        let mut chain = vec![self.language.clone()];
+        while let Some(next) = chain
+            .last()
+            .and_then(|language| self.files.get(language))
+            .and_then(|handle| files.get(handle))
+            .and_then(|file| file.fallback.clone())
+        {
+            if chain.contains(&next) {
+                break;
+            }
+            chain.push(next);
+        }
+        if !chain.contains(&self.default_language) {
+            chain.push(self.default_language.clone());
+        }
+        chain
+    }
+
+    fn rebuild(&mut self, files: &Assets<LocaleFile>, asset_server: &AssetServer) {
+        let chain = self.chain(files);
+        self.entries.clear();
+        self.fonts.clear();
+        // 先放回退的语言 再用前面的覆盖
+        for language in chain.iter().rev() {
+            if let Some(file) = self.files.get(language).and_then(|h| files.get(h)) {
+                for (key, value) in &file.entries {
+                    self.entries.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        for language in &chain {
+            if let Some(file) = self.files.get(language).and_then(|h| files.get(h)) {
+                self.fonts.extend(
+                    file.fonts
+                        .iter()
+                        .map(|path| asset_server.load(path.clone())),
+                );
+            }
+        }
+        self.resolve_font(asset_server);
+    }
+
+    // 字体链里第一个没有加载失败的 都失败了用默认字体
+    fn resolve_font(&mut self, asset_server: &AssetServer) -> bool {
+        let font = self
+            .fonts
+            .iter()
+            .find(|font| !matches!(asset_server.load_state(*font), LoadState::Failed(_)))
+            .cloned()
+            .unwrap_or_default();
+        let changed = font != self.font;
+        self.font = font;
+        changed
+    }
+}
+
+// 界面上需要翻译的文本 语言变化时自动刷新
+#[derive(Component, Debug, Clone)]
+pub struct LocalizedText {
+    pub key: String,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl LocalizedText {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            args: vec![],
+        }
+    }
+
+    pub fn with_arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    pub fn resolve(&self, localization: &Localization) -> String {
+        localization.format(&self.key, &self.args)
+    }
+}
+
+// 文本内容由各自的系统刷新 只跟随语言切换字体
+#[derive(Component, Debug, Default)]
+pub struct LocalizedFont;
+
+// 卡片定义的编号 名字和规则文本从语言文件的 card.<id>.name / card.<id>.rules 读取
+#[derive(Component, Debug, Clone)]
+pub struct CardId(pub String);
+
+impl CardId {
+    pub fn name_key(&self) -> String {
+        format!("card.{}.name", self.0)
+    }
+
+    pub fn rules_key(&self) -> String {
+        format!("card.{}.rules", self.0)
+    }
+}
+
+pub fn load_locales(mut localization: ResMut<Localization>, asset_server: Res<AssetServer>) {
+    let languages = localization.languages.clone();
+    for language in languages {
+        let handle = asset_server.load(format!("locales/{}.lang", language));
+        localization.files.insert(language, handle);
+    }
+}
+
+pub fn rebuild_localization_system(
+    mut localization: ResMut<Localization>,
+    asset_server: Res<AssetServer>,
+    files: Res<Assets<LocaleFile>>,
+    mut locale_events: EventReader<AssetEvent<LocaleFile>>,
+    mut locale_failed: EventReader<AssetLoadFailedEvent<LocaleFile>>,
+    mut font_failed: EventReader<AssetLoadFailedEvent<Font>>,
+) {
+    for failed in locale_failed.read() {
+        warn!("语言文件加载失败 {}: {}", failed.path, failed.error);
+    }
+    let reload = locale_events.read().count() > 0;
+    if reload || localization.bypass_change_detection().dirty {
+        let localization = localization.as_mut();
+        localization.dirty = false;
+        localization.rebuild(&files, &asset_server);
+        return;
+    }
+    // 字体加载失败时换到链上的下一个
+    let mut font_changed = false;
+    for failed in font_failed.read() {
+        warn!("字体加载失败 {}: {}", failed.path, failed.error);
+        font_changed = true;
+    }
+    if font_changed
+        && localization
+            .bypass_change_detection()
+            .resolve_font(&asset_server)
+    {
+        localization.set_changed();
+    }
+}
+
+pub fn update_localized_text(
+    localization: Res<Localization>,
+    mut texts: Query<(Ref<LocalizedText>, &mut Text, Option<&mut TextFont>)>,
+    mut fonts: Query<&mut TextFont, (With<LocalizedFont>, Without<LocalizedText>)>,
+) {
+    let refresh = localization.is_changed();
+    for (localized, mut text, font) in texts.iter_mut() {
+        if !refresh && !localized.is_changed() {
+            continue;
+        }
+        let value = localized.resolve(&localization);
+        if text.0 != value {
+            text.0 = value;
+        }
+        if let Some(mut font) = font.filter(|font| font.font != localization.font) {
+            font.font = localization.font();
+        }
+    }
+    if refresh {
+        for mut font in fonts.iter_mut() {
+            if font.font != localization.font {
+                font.font = localization.font();
+            }
+        }
+    }
+}
+
+// 卡片编号变化或者切换语言时重新生成卡片文本
+pub fn localize_card_text(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    cards: Query<(Entity, Ref<CardId>)>,
+) {
+    for (entity, id) in cards.iter() {
+        if !localization.is_changed() && !id.is_changed() {
+            continue;
+        }
+        commands.entity(entity).insert(CardText {
+            name: localization.get(&id.name_key()).to_string(),
+            rules: localization.get(&id.rules_key()).to_string(),
+        });
+    }
+}
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LocaleFile>()
+            .init_asset_loader::<LocaleLoader>()
+            .init_resource::<Localization>()
+            .add_systems(Startup, load_locales)
+            .add_systems(
+                PreUpdate,
+                (
+                    rebuild_localization_system,
+                    (update_localized_text, localize_card_text),
+                )
+                    .chain(),
+            );
+    }
+}
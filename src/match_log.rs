@@ -0,0 +1,593 @@
+use crate::actions::{ActionState, InputAction};
+use crate::game::{Player, TurnState};
+use crate::locale::{Localization, LocalizedFont, LocalizedText};
+use crate::theme::{
+    ButtonRole, FontRole, PanelRole, TextRole, ThemedButton, ThemedButtonText, ThemedPanel,
+    ThemedText, UiTheme,
+};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::picking::focus::HoverMap;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+const LOG_EXPORT_PATH: &str = "match_log.txt";
+// 滚轮一行的像素
+const LOG_LINE_HEIGHT: f32 = 22.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogKind {
+    Turn,
+    Play,
+    Ability,
+    Damage,
+    Draw,
+}
+
+impl LogKind {
+    pub const ALL: [LogKind; 5] = [
+        LogKind::Turn,
+        LogKind::Play,
+        LogKind::Ability,
+        LogKind::Damage,
+        LogKind::Draw,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            LogKind::Turn => "log.kind.turn",
+            LogKind::Play => "log.kind.play",
+            LogKind::Ability => "log.kind.ability",
+            LogKind::Damage => "log.kind.damage",
+            LogKind::Draw => "log.kind.draw",
+        }
+    }
+}
+
+// 一条对局记录 引用的卡片和场地用于悬停高亮
+// 文本存的是语言 key 和参数 切换语言后整个记录都会跟着变
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub turn: u32,
+    pub kind: LogKind,
+    pub player: Option<Entity>,
+    pub card: Option<Entity>,
+    pub zone: Option<Entity>,
+    pub text: LocalizedText,
+}
+
+impl LogEntry {
+    pub fn new(kind: LogKind, key: impl Into<String>) -> Self {
+        Self {
+            turn: 0,
+            kind,
+            player: None,
+            card: None,
+            zone: None,
+            text: LocalizedText::new(key),
+        }
+    }
+
+    pub fn card_played(card: Entity, zone: Entity) -> Self {
+        Self::new(LogKind::Play, "log.card_played")
+            .with_card(card)
+            .with_zone(zone)
+    }
+
+    pub fn ability(card: Entity, key: impl Into<String>) -> Self {
+        Self::new(LogKind::Ability, key).with_card(card)
+    }
+
+    pub fn damage(target: Entity, amount: i32) -> Self {
+        Self::new(LogKind::Damage, "log.damage")
+            .with_player(target)
+            .with_arg("amount", amount)
+    }
+
+    pub fn draw(player: Entity, count: u32) -> Self {
+        Self::new(LogKind::Draw, "log.draw")
+            .with_player(player)
+            .with_arg("count", count)
+    }
+
+    pub fn with_arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.text = self.text.with_arg(name, value);
+        self
+    }
+
+    pub fn describe(&self, localization: &Localization, players: &Query<&Player>) -> String {
+        let name = self
+            .player
+            .and_then(|player| players.get(player).ok())
+            .map_or("", |player| localization.get(&player.name));
+        format!(
+            "{} [{}] {} {}",
+            self.turn,
+            localization.get(self.kind.key()),
+            name,
+            self.text.resolve(localization)
+        )
+    }
+
+    pub fn with_player(mut self, player: Entity) -> Self {
+        self.player = Some(player);
+        self
+    }
+
+    pub fn with_card(mut self, card: Entity) -> Self {
+        self.card = Some(card);
+        self
+    }
+
+    pub fn with_zone(mut self, zone: Entity) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+}
+
+// 对局记录和当前的筛选条件 面板在它变化时重建
+#[derive(Resource, Debug, Default)]
+pub struct MatchLog {
+    entries: Vec<LogEntry>,
+    pub player_filter: Option<Entity>,
+    pub hidden_kinds: HashSet<LogKind>,
+}
+
+impl MatchLog {
+    pub fn push(&mut self, entry: LogEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    pub fn is_visible(&self, entry: &LogEntry) -> bool {
+        if self.hidden_kinds.contains(&entry.kind) {
+            return false;
+        }
+        match self.player_filter {
+            Some(player) => entry.player == Some(player),
+            None => true,
+        }
+    }
+
+    pub fn visible(&self) -> impl Iterator<Item = (usize, &LogEntry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| self.is_visible(entry))
+    }
+
+    pub fn toggle_kind(&mut self, kind: LogKind) {
+        if !self.hidden_kinds.remove(&kind) {
+            self.hidden_kinds.insert(kind);
+        }
+    }
+
+    // 全部记录导出成文本 不受筛选影响
+    pub fn export(
+        &self,
+        path: &Path,
+        localization: &Localization,
+        players: &Query<&Player>,
+    ) -> std::io::Result<()> {
+        let mut text = String::new();
+        for entry in &self.entries {
+            let _ = writeln!(text, "{}", entry.describe(localization, players));
+        }
+        std::fs::write(path, text)
+    }
+}
+
+pub trait MatchLogExt {
+    // 记录时补上当前回合
+    fn log_event(&mut self, entry: LogEntry);
+}
+
+impl MatchLogExt for Commands<'_, '_> {
+    fn log_event(&mut self, mut entry: LogEntry) {
+        self.queue(move |world: &mut World| {
+            if let Some(turn) = world.get_resource::<TurnState>() {
+                entry.turn = turn.turn;
+            }
+            if let Some(mut log) = world.get_resource_mut::<MatchLog>() {
+                log.push(entry);
+            }
+        });
+    }
+}
+
+// 悬停记录时高亮的卡片和场地
+#[derive(Resource, Debug, Default)]
+pub struct LogHighlight {
+    pub card: Option<Entity>,
+    pub zone: Option<Entity>,
+}
+
+#[derive(Component, Debug)]
+pub struct LogPanel;
+
+#[derive(Component, Debug)]
+pub struct LogList;
+
+#[derive(Component, Debug)]
+pub struct LogRow {
+    pub index: usize,
+}
+
+#[derive(Component, Debug)]
+pub struct PlayerFilterButton;
+
+#[derive(Component, Debug)]
+pub struct KindFilterButton(pub LogKind);
+
+#[derive(Component, Debug)]
+pub struct ExportLogButton;
+
+fn spawn_log_button<'a>(
+    parent: &'a mut ChildBuilder,
+    marker: impl Component,
+    role: ButtonRole,
+    label: &str,
+    localization: &Localization,
+) -> EntityCommands<'a> {
+    let mut button = parent.spawn((
+        Button,
+        marker,
+        ThemedButton::new(role),
+        Node {
+            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+            ..default()
+        },
+    ));
+    button.with_children(|btn| {
+        btn.spawn((
+            Text::new(localization.get(label)),
+            LocalizedText::new(label),
+            TextFont {
+                font: localization.font(),
+                ..default()
+            },
+            ThemedButtonText(FontRole::Tiny),
+            PickingBehavior::IGNORE,
+        ));
+    });
+    button
+}
+
+pub fn setup_log_panel(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    theme: Res<UiTheme>,
+) {
+    commands
+        .spawn((
+            LogPanel,
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(theme.spacing.large),
+                bottom: Val::Px(140.0),
+                width: Val::Px(340.0),
+                height: Val::Px(320.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(theme.spacing.medium)),
+                row_gap: Val::Px(theme.spacing.small),
+                ..default()
+            },
+            ThemedPanel(PanelRole::Panel),
+        ))
+        .with_children(|parent| {
+            // 筛选和导出
+            parent
+                .spawn(Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: Val::Px(theme.spacing.small),
+                    row_gap: Val::Px(theme.spacing.small),
+                    ..default()
+                })
+                .with_children(|bar| {
+                    spawn_log_button(
+                        bar,
+                        PlayerFilterButton,
+                        ButtonRole::Primary,
+                        "log.all_players",
+                        &localization,
+                    )
+                    .observe(on_player_filter_click);
+                    for kind in LogKind::ALL {
+                        spawn_log_button(
+                            bar,
+                            KindFilterButton(kind),
+                            ButtonRole::Item,
+                            kind.key(),
+                            &localization,
+                        )
+                        .observe(on_kind_filter_click);
+                    }
+                    spawn_log_button(
+                        bar,
+                        ExportLogButton,
+                        ButtonRole::Primary,
+                        "log.export",
+                        &localization,
+                    )
+                    .observe(on_export_click);
+                });
+            parent.spawn((
+                LogList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    flex_grow: 1.0,
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                ScrollPosition::default(),
+            ));
+        });
+}
+
+// 依次切换 全部 -> 每个玩家 -> 全部
+fn on_player_filter_click(
+    _click: Trigger<Pointer<Click>>,
+    mut log: ResMut<MatchLog>,
+    players: Query<(Entity, &Player)>,
+) {
+    let mut seats: Vec<(usize, Entity)> = players
+        .iter()
+        .map(|(entity, player)| (player.seat, entity))
+        .collect();
+    seats.sort();
+    let next = match log.player_filter {
+        None => seats.first().map(|(_, entity)| *entity),
+        Some(current) => seats
+            .iter()
+            .position(|(_, entity)| *entity == current)
+            .and_then(|i| seats.get(i + 1))
+            .map(|(_, entity)| *entity),
+    };
+    log.player_filter = next;
+}
+
+fn on_kind_filter_click(
+    click: Trigger<Pointer<Click>>,
+    buttons: Query<&KindFilterButton>,
+    mut log: ResMut<MatchLog>,
+) {
+    if let Ok(button) = buttons.get(click.entity()) {
+        log.toggle_kind(button.0);
+    }
+}
+
+fn on_export_click(
+    _click: Trigger<Pointer<Click>>,
+    log: Res<MatchLog>,
+    localization: Res<Localization>,
+    players: Query<&Player>,
+) {
+    let path = PathBuf::from(LOG_EXPORT_PATH);
+    match log.export(&path, &localization, &players) {
+        Ok(()) => info!("对局记录已导出到 {}", path.display()),
+        Err(err) => error!("导出对局记录失败 {}: {}", path.display(), err),
+    }
+}
+
+// 记录或筛选变化时重建列表 并滚动到最底下
+#[allow(clippy::too_many_arguments)]
+pub fn rebuild_log_system(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    log: Res<MatchLog>,
+    players: Query<&Player>,
+    mut lists: Query<(Entity, &mut ScrollPosition), With<LogList>>,
+    player_buttons: Query<&Children, With<PlayerFilterButton>>,
+    mut kind_buttons: Query<(&KindFilterButton, &mut ThemedButton)>,
+    mut labels: Query<&mut LocalizedText>,
+) {
+    if !log.is_changed() && !localization.is_changed() {
+        return;
+    }
+    for (list, mut scroll) in lists.iter_mut() {
+        commands.entity(list).despawn_descendants();
+        commands.entity(list).with_children(|parent| {
+            for (index, entry) in log.visible() {
+                parent
+                    .spawn((
+                        LogRow { index },
+                        LocalizedFont,
+                        Text::new(entry.describe(&localization, &players)),
+                        TextFont {
+                            font: localization.font(),
+                            ..default()
+                        },
+                        ThemedText::new(FontRole::Tiny, TextRole::Light),
+                    ))
+                    .observe(on_row_over)
+                    .observe(on_row_out);
+            }
+        });
+        // 布局时会限制在最大值
+        scroll.offset_y = f32::MAX;
+    }
+
+    for children in player_buttons.iter() {
+        let key = log
+            .player_filter
+            .and_then(|player| players.get(player).ok())
+            .map_or("log.all_players".to_string(), |player| player.name.clone());
+        let mut iter = labels.iter_many_mut(children);
+        while let Some(mut label) = iter.fetch_next() {
+            if label.key != key {
+                label.key = key.clone();
+            }
+        }
+    }
+    // 显示中的种类高亮
+    for (filter, mut button) in kind_buttons.iter_mut() {
+        let selected = !log.hidden_kinds.contains(&filter.0);
+        if button.selected != selected {
+            button.selected = selected;
+        }
+    }
+}
+
+fn on_row_over(
+    over: Trigger<Pointer<Over>>,
+    rows: Query<&LogRow>,
+    log: Res<MatchLog>,
+    mut highlight: ResMut<LogHighlight>,
+) {
+    let Ok(row) = rows.get(over.entity()) else {
+        return;
+    };
+    if let Some(entry) = log.entries().get(row.index) {
+        highlight.card = entry.card;
+        highlight.zone = entry.zone;
+    }
+}
+
+fn on_row_out(_out: Trigger<Pointer<Out>>, mut highlight: ResMut<LogHighlight>) {
+    highlight.card = None;
+    highlight.zone = None;
+}
+
+pub fn log_highlight_system(
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+    highlight: Res<LogHighlight>,
+    transforms: Query<&GlobalTransform>,
+) {
+    for (entity, color) in [
+        (highlight.card, theme.palette.accent),
+        (highlight.zone, theme.palette.highlight),
+    ] {
+        let Some(transform) = entity.and_then(|entity| transforms.get(entity).ok()) else {
+            continue;
+        };
+        gizmos.circle(
+            Isometry3d::new(
+                transform.translation() + Vec3::Z * 0.1,
+                transform.rotation(),
+            ),
+            2.2,
+            color,
+        );
+    }
+}
+
+// 鼠标在列表上时用滚轮翻动 同一帧的滚动全部累加
+// 面板的节点都能被拾取 镜头缩放看到鼠标在界面上就不会再用这些滚动
+pub fn log_scroll_system(
+    mut wheel: EventReader<MouseWheel>,
+    hover_map: Res<HoverMap>,
+    parents: Query<&Parent>,
+    mut lists: Query<&mut ScrollPosition, With<LogList>>,
+) {
+    let dy: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * LOG_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+    if dy == 0.0 {
+        return;
+    }
+    let list = hover_map
+        .values()
+        .flat_map(|hits| hits.keys())
+        .find_map(|hovered| {
+            std::iter::once(*hovered)
+                .chain(parents.iter_ancestors(*hovered))
+                .find(|entity| lists.contains(*entity))
+        });
+    if let Some(mut scroll) = list.and_then(|list| lists.get_mut(list).ok()) {
+        scroll.offset_y = (scroll.offset_y - dy).max(0.0);
+    }
+}
+
+// 显示或隐藏记录面板 默认是 L
+pub fn toggle_log_system(actions: Res<ActionState>, mut panels: Query<&mut Node, With<LogPanel>>) {
+    if !actions.just_pressed(InputAction::ToggleLog) {
+        return;
+    }
+    for mut node in panels.iter_mut() {
+        node.display = match node.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+pub struct MatchLogPlugin;
+
+impl Plugin for MatchLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchLog>()
+            .init_resource::<LogHighlight>()
+            .add_systems(Startup, setup_log_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_log_system,
+                    log_scroll_system,
+                    rebuild_log_system,
+                    log_highlight_system,
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera_rig::{camera_pan_zoom_system, CameraRig};
+    use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll};
+    use bevy::picking::backend::HitData;
+    use bevy::picking::pointer::PointerId;
+    use bevy::utils::HashMap;
+
+    #[test]
+    fn wheel_over_log_scrolls_list_not_camera() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<MouseWheel>()
+            .init_resource::<HoverMap>()
+            .init_resource::<ActionState>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<AccumulatedMouseMotion>()
+            .init_resource::<AccumulatedMouseScroll>()
+            .add_systems(Update, (log_scroll_system, camera_pan_zoom_system));
+        let camera = app.world_mut().spawn(CameraRig::default()).id();
+        let list = app
+            .world_mut()
+            .spawn((LogList, Node::default(), ScrollPosition::default()))
+            .id();
+        let row = app.world_mut().spawn(Node::default()).set_parent(list).id();
+        app.world_mut().resource_mut::<HoverMap>().insert(
+            PointerId::Mouse,
+            HashMap::from_iter([(row, HitData::new(camera, 0.0, None, None))]),
+        );
+        // 同一帧两次滚动都要算上
+        for _ in 0..2 {
+            app.world_mut().send_event(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y: -1.0,
+                window: Entity::PLACEHOLDER,
+            });
+        }
+        *app.world_mut().resource_mut::<AccumulatedMouseScroll>() = AccumulatedMouseScroll {
+            unit: MouseScrollUnit::Line,
+            delta: Vec2::new(0.0, -2.0),
+        };
+        app.update();
+
+        let scroll = app.world().get::<ScrollPosition>(list).unwrap();
+        assert_eq!(scroll.offset_y, 2.0 * LOG_LINE_HEIGHT);
+        // 鼠标在记录上 镜头不缩放
+        let rig = app.world().get::<CameraRig>(camera).unwrap();
+        assert_eq!(rig.pose.distance, CameraRig::default().pose.distance);
+    }
+}
@@ -0,0 +1,451 @@
+use crate::cards::{Card, CardInfo, CardLocation, CardText, Setted};
+use crate::cases::{CaseZone, CaseZoneType};
+use crate::dialog::{Dialog, DialogAction, DialogBody, DialogResult};
+use crate::game::{Life, Owner, Player, ZoneCounts};
+use crate::locale::CardId;
+use crate::match_log::{LogEntry, LogKind, MatchLogExt};
+use crate::presentation::{Presentation, PresentationExt};
+use bevy::prelude::*;
+
+// 请求把卡片打到某个场地上 拖拽和键盘操作都从这里进入
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayRequested {
+    pub card: Entity,
+    pub zone: Entity,
+}
+
+// 多张卡一起出牌 只弹一个确认对话框
+// with 是已经在等待确认的卡片 这一组加入它的对话框
+#[derive(Event, Debug, Clone)]
+pub struct GroupPlayRequested {
+    pub plays: Vec<PlayRequested>,
+    pub with: Option<Entity>,
+}
+
+// 请求弃掉一张卡 多选时每张卡单独请求
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DiscardRequested {
+    pub card: Entity,
+}
+
+// 请求把卡片直接移到某个场地 不经过确认 目前只能移到空的安全区
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MoveRequested {
+    pub card: Entity,
+    pub zone: Entity,
+}
+
+// 登场爆炸的伤害
+const BOOM_DAMAGE: i32 = 1;
+
+// 弃掉的卡片飞去的位置
+const GRAVEYARD_POSITION: Vec3 = Vec3::new(14.0, -7.0, 1.0);
+
+// 已经放到场地上 等待对话框确认的出牌
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PendingPlay {
+    pub zone: Entity,
+    // 回滚时恢复的位置
+    pub previous: CardLocation,
+}
+
+// 出牌确认对话框 记录对应的卡片 一起出的卡片一起确认
+#[derive(Component, Debug)]
+pub struct PlayDialog {
+    pub cards: Vec<Entity>,
+}
+
+// 场地上已经有别的卡片 包括等待确认的
+pub fn zone_occupied(
+    locations: &Query<(Entity, &CardLocation)>,
+    card: Entity,
+    zone: Entity,
+) -> bool {
+    locations
+        .iter()
+        .any(|(other, location)| other != card && *location == CardLocation::Zone(zone))
+}
+
+pub fn on_play_requested(trigger: Trigger<PlayRequested>, mut commands: Commands) {
+    commands.trigger(GroupPlayRequested {
+        plays: vec![*trigger.event()],
+        with: None,
+    });
+}
+
+// 每张卡单独检查 目标场地被占用或者和前面的卡重复时不出
+// 没出的卡片由发出请求的一方放回去 拖拽的弹回手里
+#[allow(clippy::type_complexity)]
+pub fn on_group_play_requested(
+    trigger: Trigger<GroupPlayRequested>,
+    mut commands: Commands,
+    cards: Query<(&CardLocation, &Children), (With<Card>, Without<Setted>, Without<PendingPlay>)>,
+    locations: Query<(Entity, &CardLocation)>,
+    zones: Query<(), With<CaseZone>>,
+    card_infos: Query<&CardInfo>,
+    mut dialogs: Query<&mut PlayDialog>,
+) {
+    let GroupPlayRequested { plays, with } = trigger.event();
+    let mut accepted: Vec<Entity> = vec![];
+    let mut claimed: Vec<Entity> = vec![];
+    for PlayRequested { card, zone } in plays.iter().copied() {
+        let Ok((location, children)) = cards.get(card) else {
+            continue;
+        };
+        let free = zones.contains(zone)
+            && !claimed.contains(&zone)
+            && !zone_occupied(&locations, card, zone);
+        if !free {
+            info!("场地已经有卡片了 {:?}", zone);
+            continue;
+        }
+        // 先暂时归到目标场地 确认之前不能再拖
+        commands.entity(card).insert((
+            PendingPlay {
+                zone,
+                previous: *location,
+            },
+            CardLocation::Zone(zone),
+        ));
+        set_card_picking(&mut commands, children, &card_infos, false);
+        claimed.push(zone);
+        accepted.push(card);
+    }
+    let Some(first) = accepted.first().copied() else {
+        return;
+    };
+
+    let joined = with.and_then(|with| {
+        dialogs
+            .iter_mut()
+            .find(|dialog| dialog.cards.contains(&with))
+    });
+    if let Some(mut dialog) = joined {
+        dialog.cards.extend(accepted);
+        return;
+    }
+    let mut dialog = Dialog::confirm("dialog.play.title");
+    let preview = cards
+        .get(first)
+        .ok()
+        .and_then(|(_, children)| card_infos.iter_many(children).next());
+    if let Some(info) = preview {
+        dialog = dialog.with_body(DialogBody::CardPreview(info.image.clone()));
+    }
+    commands
+        .spawn((dialog, PlayDialog { cards: accepted }))
+        .observe(resolve_pending_play);
+}
+
+#[allow(clippy::type_complexity)]
+pub fn resolve_pending_play(
+    result: Trigger<DialogResult>,
+    mut commands: Commands,
+    dialogs: Query<&PlayDialog>,
+    cards: Query<(
+        &Card,
+        &PendingPlay,
+        &Children,
+        Option<&CardId>,
+        Option<&CardText>,
+        Option<&Owner>,
+    )>,
+    zones: Query<&CaseZone>,
+    card_infos: Query<&CardInfo>,
+) {
+    let Ok(dialog) = dialogs.get(result.dialog) else {
+        return;
+    };
+    for card in dialog.cards.iter().copied() {
+        let Ok((card_state, pending, children, id, text, owner)) = cards.get(card) else {
+            continue;
+        };
+        commands.entity(card).remove::<PendingPlay>();
+        set_card_picking(&mut commands, children, &card_infos, true);
+
+        let zone = zones.get(pending.zone);
+        match (result.action, zone) {
+            (DialogAction::Confirm, Ok(zone)) => {
+                let mut entry = LogEntry::card_played(card, pending.zone)
+                    .with_arg("card", card_name(id, text))
+                    .with_arg("zone", zone.zone_type.key())
+                    .with_arg("num", zone.num);
+                if let Some(owner) = owner {
+                    entry = entry.with_player(owner.0);
+                }
+                commands.log_event(entry);
+                commit_play(&mut commands, card, card_state, zone);
+            }
+            _ => {
+                info!("取消");
+                // 回滚 恢复原来的位置并且飞回手里
+                commands.entity(card).insert(pending.previous);
+                commands.present(Presentation::move_card(
+                    card,
+                    card_state.trans.translation,
+                    0.6,
+                ));
+            }
+        }
+    }
+}
+
+// 有编号时记录 key 切换语言后名字也会跟着变
+fn card_name(id: Option<&CardId>, text: Option<&CardText>) -> String {
+    match (id, text) {
+        (Some(id), _) => id.name_key(),
+        (None, Some(text)) => text.name.clone(),
+        (None, None) => "prompt.card".to_string(),
+    }
+}
+
+fn commit_play(commands: &mut Commands, card: Entity, card_state: &Card, zone: &CaseZone) {
+    let end = zone.transform.translation;
+    let mut mid = Vec3::ZERO;
+    mid.z = card_state.trans.translation.z;
+
+    let mut mid2 = Vec3::ZERO;
+    mid2.z = card_state.trans.translation.z + 3.0;
+    let card_end = Card {
+        trans: Transform::from_translation(end),
+    };
+    commands.present_all([
+        Presentation::move_card(card, mid, 1.0),
+        Presentation::move_card(card, mid2, 1.0),
+        Presentation::move_card(card, end, 0.6),
+        // 卡片落地之后才算登场
+        Presentation::call(move |cmd| {
+            cmd.entity(card).insert(card_end.clone()).insert(Setted);
+            cmd.log_event(LogEntry::ability(card, "log.ability.boom"));
+        }),
+        Presentation::Parallel(vec![
+            Presentation::effect_at("boom", card),
+            Presentation::Effect("shark"),
+        ]),
+        // 爆炸打到对手
+        Presentation::call(move |cmd| {
+            cmd.queue(move |world: &mut World| boom_damage(world, card));
+        }),
+    ]);
+}
+
+// 登场爆炸对主人以外的每个玩家造成伤害 没有主人的卡片不打人
+fn boom_damage(world: &mut World, card: Entity) {
+    let Some(owner) = world.get::<Owner>(card).map(|owner| owner.0) else {
+        return;
+    };
+    let mut players = world.query_filtered::<(Entity, &mut Life), With<Player>>();
+    let mut targets = vec![];
+    for (player, mut life) in players.iter_mut(world) {
+        if player != owner {
+            life.0 -= BOOM_DAMAGE;
+            targets.push(player);
+        }
+    }
+    let mut commands = world.commands();
+    for player in targets {
+        commands.log_event(LogEntry::damage(player, BOOM_DAMAGE).with_card(card));
+    }
+}
+
+// 卡面是拾取的对象 关掉之后卡片就不能被拖动
+fn set_card_picking(
+    commands: &mut Commands,
+    children: &Children,
+    card_infos: &Query<&CardInfo>,
+    enabled: bool,
+) {
+    for child in children.iter() {
+        if card_infos.get(*child).is_err() {
+            continue;
+        }
+        if enabled {
+            commands.entity(*child).remove::<PickingBehavior>();
+        } else {
+            commands.entity(*child).insert(PickingBehavior::IGNORE);
+        }
+    }
+}
+
+// 等待确认的卡片不能弃掉 弃掉后计入主人的弃牌堆
+#[allow(clippy::type_complexity)]
+pub fn on_discard_requested(
+    trigger: Trigger<DiscardRequested>,
+    mut commands: Commands,
+    cards: Query<(Option<&CardId>, Option<&CardText>, Option<&Owner>), (With<Card>, Without<PendingPlay>)>,
+    mut counts: Query<&mut ZoneCounts>,
+) {
+    let card = trigger.event().card;
+    let Ok((id, text, owner)) = cards.get(card) else {
+        info!("不能弃掉 {:?}", card);
+        return;
+    };
+    let mut entry = LogEntry::new(LogKind::Play, "log.discard")
+        .with_card(card)
+        .with_arg("card", card_name(id, text));
+    if let Some(owner) = owner {
+        entry = entry.with_player(owner.0);
+        if let Ok(mut counts) = counts.get_mut(owner.0) {
+            counts.graveyard += 1;
+        }
+    }
+    commands.log_event(entry);
+    // 先离开手牌和场地 飞出去之后再删除
+    commands
+        .entity(card)
+        .remove::<CardLocation>()
+        .insert(PickingBehavior::IGNORE);
+    commands.present_all([
+        Presentation::move_card(card, GRAVEYARD_POSITION, 0.5),
+        Presentation::call(move |cmd| {
+            if let Some(entity) = cmd.get_entity(card) {
+                entity.despawn_recursive();
+            }
+        }),
+    ]);
+}
+
+// 目标必须是空的安全区 同一帧的多个请求依次检查 前面的结果对后面可见
+#[allow(clippy::type_complexity)]
+pub fn on_move_requested(
+    trigger: Trigger<MoveRequested>,
+    mut commands: Commands,
+    cards: Query<
+        (&Card, Option<&CardId>, Option<&CardText>, Option<&Owner>),
+        Without<PendingPlay>,
+    >,
+    locations: Query<(Entity, &CardLocation)>,
+    zones: Query<&CaseZone>,
+) {
+    let MoveRequested { card, zone } = *trigger.event();
+    let Ok((card_state, id, text, owner)) = cards.get(card) else {
+        return;
+    };
+    let Ok(case_zone) = zones.get(zone) else {
+        return;
+    };
+    if !matches!(case_zone.zone_type, CaseZoneType::Safe) {
+        info!("只能移到安全区 {:?}", zone);
+        return;
+    }
+    if zone_occupied(&locations, card, zone) {
+        info!("安全区已经有卡片了 {:?}", zone);
+        return;
+    }
+    let mut entry = LogEntry::new(LogKind::Play, "log.moved")
+        .with_card(card)
+        .with_zone(zone)
+        .with_arg("card", card_name(id, text))
+        .with_arg("zone", case_zone.zone_type.key())
+        .with_arg("num", case_zone.num);
+    if let Some(owner) = owner {
+        entry = entry.with_player(owner.0);
+    }
+    commands.log_event(entry);
+    let end = case_zone.transform.translation;
+    let mut moved = card_state.clone();
+    moved.trans.translation = end;
+    commands
+        .entity(card)
+        .insert((CardLocation::Zone(zone), moved, Setted));
+    commands.present(Presentation::move_card(card, end, 0.6));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(world: &mut World, num: u32) -> Entity {
+        world
+            .spawn(CaseZone {
+                zone_type: CaseZoneType::Battle,
+                transform: Transform::default(),
+                num,
+            })
+            .id()
+    }
+
+    fn card(world: &mut World, location: CardLocation) -> Entity {
+        world
+            .spawn((Card::default(), location))
+            .with_children(|card| {
+                card.spawn(CardInfo {
+                    image: Handle::default(),
+                });
+            })
+            .id()
+    }
+
+    fn pending(world: &mut World) -> Vec<(Entity, Entity)> {
+        let mut query = world.query::<(Entity, &PendingPlay)>();
+        let mut pending: Vec<_> = query
+            .iter(world)
+            .map(|(card, pending)| (card, pending.zone))
+            .collect();
+        pending.sort();
+        pending
+    }
+
+    fn dialogs(world: &mut World) -> Vec<Vec<Entity>> {
+        let mut query = world.query::<&PlayDialog>();
+        query
+            .iter(world)
+            .map(|dialog| dialog.cards.clone())
+            .collect()
+    }
+
+    #[test]
+    fn group_play_skips_taken_zones_and_asks_once() {
+        let mut world = World::new();
+        world.add_observer(on_play_requested);
+        world.add_observer(on_group_play_requested);
+        let taken = zone(&mut world, 1);
+        let free = zone(&mut world, 2);
+        let other = zone(&mut world, 3);
+        card(&mut world, CardLocation::Zone(taken));
+        let first = card(&mut world, CardLocation::Hand);
+        let second = card(&mut world, CardLocation::Hand);
+        let third = card(&mut world, CardLocation::Hand);
+
+        world.trigger(GroupPlayRequested {
+            plays: vec![
+                PlayRequested {
+                    card: first,
+                    zone: taken,
+                },
+                PlayRequested {
+                    card: second,
+                    zone: free,
+                },
+                PlayRequested {
+                    card: third,
+                    zone: free,
+                },
+            ],
+            with: None,
+        });
+        world.flush();
+        assert_eq!(pending(&mut world), [(second, free)]);
+        assert_eq!(dialogs(&mut world), [vec![second]]);
+
+        // 加入已经打开的对话框 不再弹新的
+        world.trigger(GroupPlayRequested {
+            plays: vec![PlayRequested {
+                card: third,
+                zone: other,
+            }],
+            with: Some(second),
+        });
+        world.flush();
+        assert_eq!(dialogs(&mut world), [vec![second, third]]);
+
+        // 单张出牌也检查占用
+        world.trigger(PlayRequested {
+            card: first,
+            zone: free,
+        });
+        world.flush();
+        assert_eq!(pending(&mut world).len(), 2);
+        assert_eq!(dialogs(&mut world).len(), 1);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::animations::{flip, FaceUp};
+use crate::effects::EffectTriggered;
+use crate::lifecycle::{AnimationFinished, AnimationLifecycleExt};
+use crate::settings::{AnimationCategory, AnimationCategoryExt};
+use bevy::prelude::*;
+use bevy_tween::combinator::{event, go, sequence, tween, TransformTargetStateExt};
+use bevy_tween::interpolation::EaseKind;
+use bevy_tween::prelude::{AnimationBuilderExt, IntoTarget};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+// 特效步骤本身的长度 事件在末尾发出
+const EFFECT_STEP: Duration = Duration::from_millis(16);
+// 跳过时的播放倍速
+const SKIP_SPEED: f32 = 1000.0;
+
+// 逻辑层发出的表现指令 由导演按顺序播放
+#[derive(Debug, Clone)]
+pub enum Presentation {
+    // 把卡片移动到目标位置 起点取播放时卡片的当前位置
+    MoveCard {
+        card: Entity,
+        to: Vec3,
+        duration: Duration,
+    },
+    // 翻到指定的一面 已经是这一面时直接跳过
+    Flip { card: Entity, face_up: bool },
+    // 触发 effect_system 里的特效
+    Effect(&'static str),
+    // 发生在某个实体上的特效 镜头跟随等按 EffectRegistry 的配置处理
+    EffectAt {
+        name: &'static str,
+        target: Entity,
+    },
+    // 停顿
+    Wait(Duration),
+    // 同时播放 全部结束后才进入下一步
+    Parallel(Vec<Presentation>),
+    // 前面的表现播完后再执行的逻辑 比如卡片落地后才算登场
+    Call(PresentationCall),
+}
+
+#[derive(Clone)]
+pub struct PresentationCall(Arc<dyn Fn(&mut Commands) + Send + Sync>);
+
+impl fmt::Debug for PresentationCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PresentationCall")
+    }
+}
+
+impl Presentation {
+    pub fn move_card(card: Entity, to: Vec3, secs: f32) -> Self {
+        Presentation::MoveCard {
+            card,
+            to,
+            duration: Duration::from_secs_f32(secs),
+        }
+    }
+
+    pub fn effect_at(name: &'static str, target: Entity) -> Self {
+        Presentation::EffectAt { name, target }
+    }
+
+    pub fn wait(secs: f32) -> Self {
+        Presentation::Wait(Duration::from_secs_f32(secs))
+    }
+
+    pub fn call(f: impl Fn(&mut Commands) + Send + Sync + 'static) -> Self {
+        Presentation::Call(PresentationCall(Arc::new(f)))
+    }
+}
+
+#[derive(Debug)]
+struct QueuedPresentation {
+    step: Presentation,
+    // 播放期间是否阻止玩家输入
+    blocking: bool,
+}
+
+#[derive(Resource, Debug)]
+pub struct PresentationSettings {
+    // 全局播放倍速
+    pub speed: f32,
+    // 跳过所有表现 每一步都瞬间完成
+    pub skip_all: bool,
+}
+
+impl Default for PresentationSettings {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            skip_all: false,
+        }
+    }
+}
+
+// 表现队列 逻辑已经结算完成 但表现可能还在排队
+#[derive(Resource, Debug, Default)]
+pub struct PresentationQueue {
+    queue: VecDeque<QueuedPresentation>,
+    // 正在播放的动画实体
+    running: Vec<Entity>,
+    running_blocking: bool,
+    // 跳过当前已经排队的内容
+    skipping: bool,
+}
+
+impl PresentationQueue {
+    pub fn push(&mut self, step: Presentation) {
+        self.queue.push_back(QueuedPresentation {
+            step,
+            blocking: true,
+        });
+    }
+
+    // 不阻止输入的表现 比如背景特效
+    pub fn push_background(&mut self, step: Presentation) {
+        self.queue.push_back(QueuedPresentation {
+            step,
+            blocking: false,
+        });
+    }
+
+    // 快进当前排队的全部表现
+    pub fn skip(&mut self) {
+        self.skipping = true;
+    }
+
+    // 没有任何表现在排队或播放
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_empty() && self.running.is_empty()
+    }
+
+    // 有需要等待的表现 此时应该屏蔽输入
+    pub fn is_blocking(&self) -> bool {
+        (!self.running.is_empty() && self.running_blocking)
+            || self.queue.iter().any(|queued| queued.blocking)
+    }
+}
+
+// 在闭包里只有 Commands 时用它来排队表现
+pub trait PresentationExt {
+    fn present(&mut self, step: Presentation) -> &mut Self;
+    fn present_all(&mut self, steps: impl IntoIterator<Item = Presentation>) -> &mut Self;
+}
+
+impl PresentationExt for Commands<'_, '_> {
+    fn present(&mut self, step: Presentation) -> &mut Self {
+        self.queue(move |world: &mut World| {
+            world.resource_mut::<PresentationQueue>().push(step);
+        });
+        self
+    }
+
+    fn present_all(&mut self, steps: impl IntoIterator<Item = Presentation>) -> &mut Self {
+        let steps: Vec<Presentation> = steps.into_iter().collect();
+        self.queue(move |world: &mut World| {
+            let mut queue = world.resource_mut::<PresentationQueue>();
+            steps.into_iter().for_each(|step| queue.push(step));
+        });
+        self
+    }
+}
+
+// 运行条件 表现不阻止输入时才处理玩家操作
+pub fn presentation_allows_input(queue: Res<PresentationQueue>) -> bool {
+    !queue.is_blocking()
+}
+
+// 导演 上一步全部播完后再开始下一步
+pub fn presentation_director_system(
+    mut commands: Commands,
+    mut queue: ResMut<PresentationQueue>,
+    settings: Res<PresentationSettings>,
+    mut animation_finished: EventReader<AnimationFinished>,
+    transforms: Query<(&Transform, Option<&FaceUp>)>,
+) {
+    for finished in animation_finished.read() {
+        if let Some(index) = queue.running.iter().position(|e| *e == finished.time_runner) {
+            queue.running.swap_remove(index);
+        }
+    }
+
+    if !queue.running.is_empty() {
+        return;
+    }
+    let Some(next) = queue.queue.pop_front() else {
+        queue.skipping = false;
+        return;
+    };
+
+    let speed = if settings.skip_all || queue.skipping {
+        SKIP_SPEED
+    } else {
+        settings.speed
+    };
+    let mut running = vec![];
+    play_step(&mut commands, &transforms, next.step, speed, &mut running);
+    queue.running = running;
+    queue.running_blocking = next.blocking;
+}
+
+fn play_step(
+    commands: &mut Commands,
+    transforms: &Query<(&Transform, Option<&FaceUp>)>,
+    step: Presentation,
+    speed: f32,
+    running: &mut Vec<Entity>,
+) {
+    match step {
+        Presentation::MoveCard { card, to, duration } => {
+            let Ok((transform, _)) = transforms.get(card) else {
+                return;
+            };
+            let mut start = card.into_target().transform_state(*transform);
+            let runner = commands
+                .animation()
+                .time_scale(speed)
+                .category(AnimationCategory::Play)
+                .notify_on_complete()
+                .despawn_on_finish()
+                .insert(tween(
+                    duration,
+                    EaseKind::ExponentialOut,
+                    start.translation_to(to),
+                ))
+                .id();
+            running.push(runner);
+        }
+        Presentation::Flip { card, face_up } => {
+            let Ok((transform, current)) = transforms.get(card) else {
+                return;
+            };
+            if current.is_some_and(|current| current.0 == face_up) {
+                return;
+            }
+            commands.entity(card).insert(FaceUp(face_up));
+            let runner = commands
+                .animation()
+                .time_scale(speed)
+                .category(AnimationCategory::Play)
+                .notify_on_complete()
+                .despawn_on_finish()
+                .insert(flip(card.into_target(), *transform))
+                .id();
+            running.push(runner);
+        }
+        Presentation::Effect(name) | Presentation::EffectAt { name, .. } => {
+            // 跳过时不需要镜头跟随
+            if speed < SKIP_SPEED {
+                let target = match step {
+                    Presentation::EffectAt { target, .. } => Some(target),
+                    _ => None,
+                };
+                commands.trigger(EffectTriggered { name, target });
+            }
+            let runner = commands
+                .animation()
+                .time_scale(speed)
+                .category(AnimationCategory::Effects)
+                .notify_on_complete()
+                .despawn_on_finish()
+                .insert(sequence((go(EFFECT_STEP), event(name))))
+                .id();
+            running.push(runner);
+        }
+        Presentation::Wait(duration) => {
+            let runner = commands
+                .animation()
+                .time_scale(speed)
+                .category(AnimationCategory::Play)
+                .notify_on_complete()
+                .despawn_on_finish()
+                .insert(go(duration))
+                .id();
+            running.push(runner);
+        }
+        Presentation::Call(call) => (call.0)(commands),
+        Presentation::Parallel(steps) => {
+            for step in steps {
+                play_step(commands, transforms, step, speed, running);
+            }
+        }
+    }
+}
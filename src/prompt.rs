@@ -0,0 +1,655 @@
+use crate::locale::{Localization, LocalizedFont, LocalizedText};
+use crate::theme::{
+    ButtonRole, FontRole, PanelRole, TextRole, ThemedButton, ThemedButtonText, ThemedPanel,
+    ThemedText, UiTheme,
+};
+use crate::{OnCancel, OnConfirm};
+use bevy::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct PromptOption {
+    // 场上的卡片或场地 列表模式下可以为空
+    pub entity: Option<Entity>,
+    pub label: String,
+}
+
+impl PromptOption {
+    pub fn entity(entity: Entity, label: impl Into<String>) -> Self {
+        Self {
+            entity: Some(entity),
+            label: label.into(),
+        }
+    }
+
+    pub fn label(label: impl Into<String>) -> Self {
+        Self {
+            entity: None,
+            label: label.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptStyle {
+    // 直接点击场上的卡片/场地
+    InWorld,
+    // 弹出一个列表
+    List,
+}
+
+// 选择 N 张/个 生成之后开始等待玩家选择 结果通过 PromptResult 返回
+#[derive(Component, Debug, Clone)]
+pub struct ChoicePrompt {
+    pub title: String,
+    pub options: Vec<PromptOption>,
+    pub min: usize,
+    pub max: usize,
+    // 选择的先后顺序有意义 比如排列牌库顶
+    pub ordered: bool,
+    pub cancellable: bool,
+    pub style: PromptStyle,
+    selected: Vec<usize>,
+}
+
+impl ChoicePrompt {
+    pub fn new(title: impl Into<String>, options: Vec<PromptOption>, style: PromptStyle) -> Self {
+        // 没有选项时不要求选择 不然永远不能确认
+        let min = options.len().min(1);
+        Self {
+            title: title.into(),
+            options,
+            min,
+            max: 1,
+            ordered: false,
+            cancellable: true,
+            style,
+            selected: vec![],
+        }
+    }
+
+    // 从场上选 N 张卡 比如弃两张
+    pub fn pick_cards(
+        title: impl Into<String>,
+        cards: Vec<PromptOption>,
+        min: usize,
+        max: usize,
+    ) -> Self {
+        Self::new(title, cards, PromptStyle::InWorld).with_count(min, max)
+    }
+
+    // 选一个场地
+    pub fn pick_zone(title: impl Into<String>, zones: Vec<PromptOption>) -> Self {
+        Self::new(title, zones, PromptStyle::InWorld)
+    }
+
+    // 多个模式选一个
+    pub fn choose_mode(title: impl Into<String>, modes: &[&str]) -> Self {
+        let options = modes
+            .iter()
+            .map(|mode| PromptOption::label(*mode))
+            .collect();
+        Self::new(title, options, PromptStyle::List)
+    }
+
+    // 按点击顺序排列全部卡片
+    pub fn order_cards(title: impl Into<String>, cards: Vec<PromptOption>) -> Self {
+        let count = cards.len();
+        let mut prompt = Self::new(title, cards, PromptStyle::List).with_count(count, count);
+        prompt.ordered = true;
+        prompt
+    }
+
+    // 不能超过选项的数量
+    pub fn with_count(mut self, min: usize, max: usize) -> Self {
+        let available = self.options.len();
+        self.min = min.min(available);
+        self.max = max.max(min).min(available);
+        self
+    }
+
+    pub fn with_style(mut self, style: PromptStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn cancellable(mut self, cancellable: bool) -> Self {
+        self.cancellable = cancellable;
+        self
+    }
+
+    pub fn selected(&self) -> &[usize] {
+        &self.selected
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (self.min..=self.max).contains(&self.selected.len())
+    }
+
+    // 再点一次取消选择 只能选一个时直接替换
+    pub fn toggle(&mut self, index: usize) {
+        if index >= self.options.len() {
+            return;
+        }
+        if let Some(position) = self.selected.iter().position(|i| *i == index) {
+            self.selected.remove(position);
+        } else if self.max == 1 {
+            self.selected = vec![index];
+        } else if self.selected.len() < self.max {
+            self.selected.push(index);
+        }
+    }
+
+    pub fn index_of(&self, entity: Entity) -> Option<usize> {
+        self.options
+            .iter()
+            .position(|option| option.entity == Some(entity))
+    }
+}
+
+// 选择结束 以选择实体为目标触发 也作为普通事件发给规则层
+#[derive(Event, Debug, Clone)]
+pub struct PromptResult {
+    pub prompt: Entity,
+    // 选中的下标 ordered 时是点击顺序
+    pub selected: Vec<usize>,
+    pub entities: Vec<Entity>,
+    pub cancelled: bool,
+}
+
+// 场上可以被选择的对象
+#[derive(Component, Debug)]
+pub struct Selectable {
+    pub prompt: Entity,
+}
+
+#[derive(Component, Debug)]
+pub struct PromptCounter {
+    pub prompt: Entity,
+}
+
+#[derive(Component, Debug)]
+pub struct PromptButton {
+    pub prompt: Entity,
+}
+
+#[derive(Component, Debug)]
+pub struct PromptListItem {
+    pub prompt: Entity,
+    pub index: usize,
+}
+
+// 运行条件 没有进行中的选择时才处理别的操作
+pub fn no_prompt_active(prompts: Query<(), With<ChoicePrompt>>) -> bool {
+    prompts.is_empty()
+}
+
+pub fn build_prompt_system(
+    mut commands: Commands,
+    localization: Res<Localization>,
+    theme: Res<UiTheme>,
+    prompts: Query<(Entity, &ChoicePrompt), Added<ChoicePrompt>>,
+) {
+    for (entity, prompt) in prompts.iter() {
+        let font = localization.font();
+        if prompt.style == PromptStyle::InWorld {
+            for option in &prompt.options {
+                if let Some(target) = option.entity {
+                    commands
+                        .entity(target)
+                        .insert(Selectable { prompt: entity });
+                }
+            }
+        }
+
+        commands
+            .entity(entity)
+            .insert((
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(theme.spacing.large),
+                    left: Val::Percent(30.0),
+                    width: Val::Percent(40.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(theme.spacing.medium)),
+                    row_gap: Val::Px(theme.spacing.medium),
+                    ..default()
+                },
+                ThemedPanel(PanelRole::Dialog),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(localization.get(&prompt.title)),
+                    LocalizedText::new(prompt.title.clone()),
+                    TextFont {
+                        font: font.clone(),
+                        ..default()
+                    },
+                    ThemedText::new(FontRole::Heading, TextRole::Dark),
+                ));
+                if prompt.style == PromptStyle::List {
+                    for (index, option) in prompt.options.iter().enumerate() {
+                        parent
+                            .spawn((
+                                Button,
+                                PromptListItem {
+                                    prompt: entity,
+                                    index,
+                                },
+                                ThemedButton::new(ButtonRole::Item),
+                                Node {
+                                    width: Val::Percent(100.0),
+                                    height: Val::Px(36.0),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                            ))
+                            .with_children(|btn| {
+                                btn.spawn((
+                                    Text::new(localization.get(&option.label)),
+                                    LocalizedText::new(option.label.clone()),
+                                    TextFont {
+                                        font: font.clone(),
+                                        ..default()
+                                    },
+                                    ThemedButtonText(FontRole::Body),
+                                ));
+                            })
+                            .observe(on_list_item_click);
+                    }
+                }
+                parent.spawn((
+                    PromptCounter { prompt: entity },
+                    LocalizedFont,
+                    Text::new(counter_text(prompt, &localization)),
+                    TextFont {
+                        font: font.clone(),
+                        ..default()
+                    },
+                    ThemedText::new(FontRole::Small, TextRole::Dark),
+                ));
+
+                // 按钮区域
+                parent
+                    .spawn(Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::SpaceEvenly,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|b_zone| {
+                        spawn_prompt_button(
+                            b_zone,
+                            entity,
+                            OnConfirm,
+                            "dialog.confirm",
+                            &localization,
+                        );
+                        if prompt.cancellable {
+                            spawn_prompt_button(
+                                b_zone,
+                                entity,
+                                OnCancel,
+                                "dialog.cancel",
+                                &localization,
+                            );
+                        }
+                    });
+            });
+    }
+}
+
+fn spawn_prompt_button(
+    b_zone: &mut ChildBuilder,
+    prompt: Entity,
+    marker: impl Component,
+    label: &str,
+    localization: &Localization,
+) {
+    b_zone
+        .spawn((
+            Button,
+            marker,
+            PromptButton { prompt },
+            ThemedButton::new(ButtonRole::Primary),
+            Node {
+                width: Val::Px(80.0),
+                height: Val::Px(40.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+        ))
+        .with_children(|btn| {
+            btn.spawn((
+                Text::new(localization.get(label)),
+                LocalizedText::new(label),
+                TextFont {
+                    font: localization.font(),
+                    ..default()
+                },
+                ThemedButtonText(FontRole::Heading),
+            ));
+        })
+        .observe(on_prompt_button_click);
+}
+
+fn counter_text(prompt: &ChoicePrompt, localization: &Localization) -> String {
+    let count = prompt.selected.len().to_string();
+    let mut text = if prompt.min == prompt.max {
+        localization.format(
+            "prompt.counter.exact",
+            &[("count", count), ("max", prompt.max.to_string())],
+        )
+    } else {
+        localization.format(
+            "prompt.counter.range",
+            &[
+                ("count", count),
+                ("min", prompt.min.to_string()),
+                ("max", prompt.max.to_string()),
+            ],
+        )
+    };
+    if prompt.ordered && !prompt.selected.is_empty() {
+        let order: Vec<&str> = prompt
+            .selected
+            .iter()
+            .map(|i| localization.get(&prompt.options[*i].label))
+            .collect();
+        text.push_str("  ");
+        text.push_str(
+            &localization.format("prompt.counter.order", &[("order", order.join(" > "))]),
+        );
+    }
+    text
+}
+
+// 点击卡面时事件会冒泡到卡片本身 只处理带 Selectable 的那一层
+pub fn on_selectable_click(
+    click: Trigger<Pointer<Click>>,
+    selectables: Query<&Selectable>,
+    mut prompts: Query<&mut ChoicePrompt>,
+) {
+    let Ok(selectable) = selectables.get(click.entity()) else {
+        return;
+    };
+    let Ok(mut prompt) = prompts.get_mut(selectable.prompt) else {
+        return;
+    };
+    if let Some(index) = prompt.index_of(click.entity()) {
+        prompt.toggle(index);
+    }
+}
+
+fn on_list_item_click(
+    click: Trigger<Pointer<Click>>,
+    items: Query<&PromptListItem>,
+    mut prompts: Query<&mut ChoicePrompt>,
+) {
+    let Ok(item) = items.get(click.entity()) else {
+        return;
+    };
+    if let Ok(mut prompt) = prompts.get_mut(item.prompt) {
+        prompt.toggle(item.index);
+    }
+}
+
+fn on_prompt_button_click(
+    click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    buttons: Query<(&PromptButton, Has<OnConfirm>, Has<OnCancel>)>,
+    prompts: Query<&ChoicePrompt>,
+    selectables: Query<(Entity, &Selectable)>,
+    mut results: EventWriter<PromptResult>,
+) {
+    let Ok((button, confirm, cancel)) = buttons.get(click.entity()) else {
+        return;
+    };
+    let Ok(prompt) = prompts.get(button.prompt) else {
+        return;
+    };
+    // 数量不对时不能确认
+    if confirm && !prompt.is_valid() {
+        return;
+    }
+    if !confirm && !cancel {
+        return;
+    }
+    let result = if confirm {
+        PromptResult {
+            prompt: button.prompt,
+            selected: prompt.selected.clone(),
+            entities: prompt
+                .selected
+                .iter()
+                .filter_map(|i| prompt.options[*i].entity)
+                .collect(),
+            cancelled: false,
+        }
+    } else {
+        PromptResult {
+            prompt: button.prompt,
+            selected: vec![],
+            entities: vec![],
+            cancelled: true,
+        }
+    };
+    commands.trigger_targets(result.clone(), button.prompt);
+    results.send(result);
+
+    for (entity, selectable) in selectables.iter() {
+        if selectable.prompt == button.prompt {
+            commands.entity(entity).remove::<Selectable>();
+        }
+    }
+    let prompt_entity = button.prompt;
+    commands.queue(move |world: &mut World| {
+        if let Ok(entity) = world.get_entity_mut(prompt_entity) {
+            entity.despawn_recursive();
+        }
+    });
+}
+
+// 刷新计数 列表选中状态 数量不对时计数显示为红色 确认按钮变灰
+#[allow(clippy::type_complexity)]
+pub fn prompt_display_system(
+    localization: Res<Localization>,
+    prompts: Query<(Entity, Ref<ChoicePrompt>)>,
+    mut counters: Query<(&PromptCounter, &mut Text, &mut ThemedText)>,
+    mut items: Query<(&PromptListItem, &mut ThemedButton)>,
+    mut buttons: Query<
+        (&PromptButton, &mut ThemedButton),
+        (With<OnConfirm>, Without<PromptListItem>),
+    >,
+) {
+    for (entity, prompt) in prompts.iter() {
+        if !prompt.is_changed() && !localization.is_changed() {
+            continue;
+        }
+        for (counter, mut text, mut themed) in counters.iter_mut() {
+            if counter.prompt != entity {
+                continue;
+            }
+            text.0 = counter_text(&prompt, &localization);
+            themed.color = if prompt.is_valid() {
+                TextRole::Dark
+            } else {
+                TextRole::Error
+            };
+        }
+        for (item, mut button) in items.iter_mut() {
+            if item.prompt != entity {
+                continue;
+            }
+            button.selected = prompt.selected.contains(&item.index);
+        }
+        for (button, mut themed) in buttons.iter_mut() {
+            if button.prompt == entity {
+                themed.disabled = !prompt.is_valid();
+            }
+        }
+    }
+}
+
+// 场上可选的对象画白圈 选中的画黄圈
+pub fn prompt_highlight_system(
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+    prompts: Query<&ChoicePrompt>,
+    selectables: Query<(Entity, &Selectable, &GlobalTransform)>,
+) {
+    for (entity, selectable, transform) in selectables.iter() {
+        let Ok(prompt) = prompts.get(selectable.prompt) else {
+            continue;
+        };
+        let selected = prompt
+            .index_of(entity)
+            .is_some_and(|index| prompt.selected.contains(&index));
+        let color = if selected {
+            theme.palette.accent
+        } else {
+            theme.palette.text_light
+        };
+        gizmos.circle(
+            Isometry3d::new(
+                transform.translation() + Vec3::Z * 0.1,
+                transform.rotation(),
+            ),
+            1.2,
+            color,
+        );
+    }
+}
+
+pub struct PromptPlugin;
+
+impl Plugin for PromptPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PromptResult>()
+            .add_observer(on_selectable_click)
+            .add_systems(
+                Update,
+                (
+                    build_prompt_system,
+                    prompt_display_system,
+                    prompt_highlight_system,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::picking::backend::HitData;
+    use bevy::picking::pointer::{Location, PointerButton, PointerId};
+    use bevy::render::camera::NormalizedRenderTarget;
+    use std::time::Duration;
+
+    fn cards(count: u32) -> Vec<PromptOption> {
+        (0..count)
+            .map(|i| PromptOption::entity(Entity::from_raw(i), format!("card.{i}")))
+            .collect()
+    }
+
+    #[test]
+    fn toggle_stops_at_max_and_retoggle_removes() {
+        let mut prompt = ChoicePrompt::pick_cards("prompt.discard", cards(3), 1, 2);
+        prompt.toggle(0);
+        prompt.toggle(1);
+        // 已经选满 第三张不算
+        prompt.toggle(2);
+        assert_eq!(prompt.selected(), &[0, 1]);
+        assert!(prompt.is_valid());
+        // 再点一次取消
+        prompt.toggle(0);
+        assert_eq!(prompt.selected(), &[1]);
+        prompt.toggle(2);
+        assert_eq!(prompt.selected(), &[1, 2]);
+        // 越界的下标不处理
+        prompt.toggle(7);
+        assert_eq!(prompt.selected(), &[1, 2]);
+    }
+
+    #[test]
+    fn single_choice_replaces_and_checks_min() {
+        let mut prompt = ChoicePrompt::pick_zone("prompt.zone", cards(3));
+        assert!(!prompt.is_valid());
+        prompt.toggle(0);
+        prompt.toggle(2);
+        assert_eq!(prompt.selected(), &[2]);
+        assert!(prompt.is_valid());
+        prompt.toggle(2);
+        assert!(prompt.selected().is_empty());
+        assert!(!prompt.is_valid());
+    }
+
+    #[test]
+    fn with_count_clamps_to_options() {
+        let prompt = ChoicePrompt::pick_cards("prompt.discard", cards(2), 3, 5);
+        assert_eq!((prompt.min, prompt.max), (2, 2));
+        // max 比 min 小时按 min 算
+        let prompt = ChoicePrompt::pick_cards("prompt.discard", cards(4), 2, 1);
+        assert_eq!((prompt.min, prompt.max), (2, 2));
+        // 没有选项时可以直接确认
+        let prompt = ChoicePrompt::pick_cards("prompt.discard", vec![], 1, 1);
+        assert_eq!((prompt.min, prompt.max), (0, 0));
+        assert!(prompt.is_valid());
+    }
+
+    #[test]
+    fn order_cards_reports_click_order() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_event::<PromptResult>();
+        let options: Vec<PromptOption> = (0..3)
+            .map(|i| PromptOption::entity(app.world_mut().spawn_empty().id(), format!("card.{i}")))
+            .collect();
+        let entities: Vec<Entity> = options.iter().filter_map(|option| option.entity).collect();
+        let mut order = ChoicePrompt::order_cards("prompt.order", options);
+        assert_eq!((order.min, order.max), (3, 3));
+        order.toggle(2);
+        order.toggle(0);
+        order.toggle(1);
+        let prompt = app.world_mut().spawn(order).id();
+        let button = app
+            .world_mut()
+            .spawn((OnConfirm, PromptButton { prompt }))
+            .observe(on_prompt_button_click)
+            .id();
+        // 观察者要等 flush 之后才生效
+        app.world_mut().flush();
+        let location = Location {
+            target: NormalizedRenderTarget::Image(Handle::default()),
+            position: Vec2::ZERO,
+        };
+        let click = Click {
+            button: PointerButton::Primary,
+            hit: HitData::new(button, 0.0, None, None),
+            duration: Duration::ZERO,
+        };
+        app.world_mut().trigger_targets(
+            Pointer::new(button, PointerId::Mouse, location, click),
+            button,
+        );
+        app.world_mut().flush();
+
+        let results: Vec<PromptResult> = app
+            .world_mut()
+            .resource_mut::<Events<PromptResult>>()
+            .drain()
+            .collect();
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].cancelled);
+        assert_eq!(results[0].selected, vec![2, 0, 1]);
+        assert_eq!(
+            results[0].entities,
+            vec![entities[2], entities[0], entities[1]]
+        );
+        // 确认后选择本身被删掉
+        assert!(app.world().get_entity(prompt).is_err());
+    }
+}
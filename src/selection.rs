@@ -0,0 +1,488 @@
+use crate::actions::{ActionState, InputAction};
+use crate::camera_rig::CameraRig;
+use crate::cards::{Card, CardInfo, CardLocation, Dragging};
+use crate::cases::{CaseZone, CaseZoneType};
+use crate::dialog::DialogStack;
+use crate::play::{DiscardRequested, MoveRequested, PendingPlay};
+use crate::presentation::PresentationQueue;
+use crate::prompt::ChoicePrompt;
+use crate::theme::UiTheme;
+use bevy::picking::focus::HoverMap;
+use bevy::picking::pointer::PointerId;
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+
+// 鼠标拖出的距离超过这么多像素才算框选
+const BOX_SELECT_MIN: f32 = 6.0;
+
+// 多选中的卡片 手牌和场上的都可以
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Selected;
+
+// 跟着另一张卡一起被拖动 offset 是开始拖拽时相对它的位置
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DragFollower {
+    pub leader: Entity,
+    pub offset: Vec3,
+}
+
+// 按住加选键点击卡片 加入或移出选择 不按时点击没选中的卡片清空选择
+pub fn select_on_click(
+    click: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    query: Query<&Parent, With<CardInfo>>,
+    cards: Query<Has<Selected>, With<Card>>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    if click.button != PointerButton::Primary || click.pointer_id.is_touch() {
+        return;
+    }
+    let Ok(parent) = query.get(click.target) else {
+        return;
+    };
+    let card = parent.get();
+    let Ok(is_selected) = cards.get(card) else {
+        return;
+    };
+    if actions.pressed(InputAction::AddToSelection) {
+        if is_selected {
+            commands.entity(card).remove::<Selected>();
+        } else {
+            commands.entity(card).insert(Selected);
+        }
+    } else if !is_selected {
+        for entity in selected.iter() {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+}
+
+// 框选的起点和屏幕上显示的方框
+#[derive(Resource, Debug, Default)]
+pub struct BoxSelect {
+    start: Option<Vec2>,
+    node: Option<Entity>,
+}
+
+#[derive(Component, Debug)]
+pub struct SelectionBoxNode;
+
+fn screen_rect(a: Vec2, b: Vec2) -> Rect {
+    Rect::from_corners(a, b)
+}
+
+// 在空白处按下左键拖出方框 松开时选中框里的卡片 按住加选键时追加
+// 没拖动就松开算点了空白处 清空选择
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn box_select_system(
+    mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    actions: Res<ActionState>,
+    theme: Res<UiTheme>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    hover_map: Res<HoverMap>,
+    blockers: Query<(), Or<(With<CardInfo>, With<Node>)>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<CameraRig>>,
+    cards: Query<(Entity, &GlobalTransform), (With<Card>, Without<PendingPlay>)>,
+    selected: Query<Entity, With<Selected>>,
+    dragging: Query<(), With<Dragging>>,
+    mut state: ResMut<BoxSelect>,
+    mut nodes: Query<&mut Node, With<SelectionBoxNode>>,
+) {
+    let Some(cursor) = windows.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+    if mouse_input.just_pressed(MouseButton::Left) {
+        // 按在卡片或者界面上时不框选
+        let blocked = hover_map
+            .get(&PointerId::Mouse)
+            .is_some_and(|hits| hits.keys().any(|entity| blockers.contains(*entity)));
+        state.start = (!blocked && dragging.is_empty()).then_some(cursor);
+    }
+    let Some(start) = state.start else {
+        return;
+    };
+    let rect = screen_rect(start, cursor);
+    let dragged = rect.size().max_element() > BOX_SELECT_MIN;
+
+    if mouse_input.pressed(MouseButton::Left) {
+        if !dragged {
+            return;
+        }
+        let node = Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(rect.min.x),
+            top: Val::Px(rect.min.y),
+            width: Val::Px(rect.width()),
+            height: Val::Px(rect.height()),
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        };
+        match state.node.and_then(|entity| nodes.get_mut(entity).ok()) {
+            Some(mut current) => *current = node,
+            None => {
+                let entity = commands
+                    .spawn((
+                        SelectionBoxNode,
+                        node,
+                        BorderColor(theme.palette.accent),
+                        BackgroundColor(theme.palette.accent.with_alpha(0.15)),
+                        PickingBehavior::IGNORE,
+                    ))
+                    .id();
+                state.node = Some(entity);
+            }
+        }
+        return;
+    }
+
+    // 松开 或者按键状态丢失
+    state.start = None;
+    if let Some(entity) = state
+        .node
+        .take()
+        .and_then(|entity| commands.get_entity(entity))
+    {
+        entity.despawn_recursive();
+    }
+    if !actions.pressed(InputAction::AddToSelection) {
+        for entity in selected.iter() {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+    if !dragged {
+        return;
+    }
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    for (entity, transform) in cards.iter() {
+        let Ok(point) = camera.world_to_viewport(camera_transform, transform.translation()) else {
+            continue;
+        };
+        if rect.contains(point) {
+            commands.entity(entity).insert(Selected);
+        }
+    }
+}
+
+// 选中的卡片画强调色的框
+pub fn selection_highlight_system(
+    mut gizmos: Gizmos,
+    theme: Res<UiTheme>,
+    selected: Query<(&GlobalTransform, Option<&Aabb>), With<Selected>>,
+) {
+    for (transform, aabb) in selected.iter() {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let size = aabb.map_or(Vec2::splat(2.0), |aabb| {
+            Vec3::from(aabb.half_extents).truncate() * 2.0
+        }) * scale.truncate();
+        gizmos.rect(
+            Isometry3d::new(translation + rotation * Vec3::Z * 0.1, rotation),
+            size + Vec2::splat(0.1),
+            theme.palette.highlight,
+        );
+    }
+}
+
+// 对选中的卡片批量操作 每张卡单独发请求 由规则层逐张检查
+// 移到安全区时每张卡找离自己最近的空安全区
+#[allow(clippy::too_many_arguments)]
+pub fn group_action_system(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    presentation: Res<PresentationQueue>,
+    dialogs: Res<DialogStack>,
+    prompts: Query<(), With<ChoicePrompt>>,
+    selected: Query<(Entity, &GlobalTransform), With<Selected>>,
+    zones: Query<(Entity, &CaseZone, &GlobalTransform)>,
+    locations: Query<&CardLocation>,
+) {
+    if actions.just_pressed(InputAction::Cancel) {
+        for (entity, _) in selected.iter() {
+            commands.entity(entity).remove::<Selected>();
+        }
+        return;
+    }
+    let discard = actions.just_pressed(InputAction::DiscardSelected);
+    let to_safe = actions.just_pressed(InputAction::MoveSelectedToSafe);
+    if !discard && !to_safe {
+        return;
+    }
+    if presentation.is_blocking() || dialogs.is_open() || !prompts.is_empty() {
+        return;
+    }
+    // 从左到右处理 结果稳定
+    let mut cards: Vec<(Entity, Vec3)> = selected
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation()))
+        .collect();
+    cards.sort_by(|a, b| a.1.x.total_cmp(&b.1.x));
+
+    if discard {
+        for (card, _) in &cards {
+            commands.trigger(DiscardRequested { card: *card });
+        }
+    } else {
+        let mut free: Vec<(Entity, Vec3)> = zones
+            .iter()
+            .filter(|(_, zone, _)| matches!(zone.zone_type, CaseZoneType::Safe))
+            .filter(|(zone, _, _)| {
+                !locations
+                    .iter()
+                    .any(|location| *location == CardLocation::Zone(*zone))
+            })
+            .map(|(entity, _, transform)| (entity, transform.translation()))
+            .collect();
+        for (card, position) in &cards {
+            // 已经在安全区的不用动
+            let in_safe = locations.get(*card).is_ok_and(|location| match location {
+                CardLocation::Zone(zone) => zones
+                    .get(*zone)
+                    .is_ok_and(|(_, zone, _)| matches!(zone.zone_type, CaseZoneType::Safe)),
+                CardLocation::Hand => false,
+            });
+            if in_safe {
+                continue;
+            }
+            let nearest = free
+                .iter()
+                .enumerate()
+                .min_by(|a, b| {
+                    let a = a.1 .1.truncate().distance(position.truncate());
+                    let b = b.1 .1.truncate().distance(position.truncate());
+                    a.total_cmp(&b)
+                })
+                .map(|(index, _)| index);
+            let Some(index) = nearest else {
+                info!("没有空的安全区了");
+                break;
+            };
+            let (zone, _) = free.remove(index);
+            commands.trigger(MoveRequested { card: *card, zone });
+        }
+    }
+    for (entity, _) in selected.iter() {
+        commands.entity(entity).remove::<Selected>();
+    }
+}
+
+// 场地在棋盘平面上的范围里包含这一点
+pub fn zone_at(
+    point: Vec3,
+    zones: &Query<(Entity, &CaseZone, &GlobalTransform, Option<&Aabb>)>,
+) -> Option<Entity> {
+    zones
+        .iter()
+        .filter(|(_, zone, _, _)| !matches!(zone.zone_type, CaseZoneType::Nothing))
+        .find(|(_, _, transform, aabb)| {
+            let half = aabb.map_or(Vec2::splat(2.0), |aabb| {
+                Vec3::from(aabb.half_extents).truncate()
+            });
+            let offset = (point - transform.translation()).truncate().abs();
+            offset.x <= half.x && offset.y <= half.y
+        })
+        .map(|(entity, _, _, _)| entity)
+}
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BoxSelect>().add_systems(
+            Update,
+            (
+                box_select_system,
+                group_action_system,
+                selection_highlight_system,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::{update_action_state, BindingCapture, InputBindings};
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::input::mouse::MouseButtonInput;
+    use bevy::input::{ButtonState, InputPlugin, InputSystem};
+    use bevy::render::camera::{camera_system, ManualTextureViews};
+    use bevy::window::{WindowCreated, WindowResized, WindowScaleFactorChanged};
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin))
+            .init_resource::<InputBindings>()
+            .init_resource::<ActionState>()
+            .init_resource::<BindingCapture>()
+            .init_resource::<HoverMap>()
+            .init_resource::<UiTheme>()
+            .init_resource::<BoxSelect>()
+            .init_resource::<PresentationQueue>()
+            .init_resource::<DialogStack>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem))
+            .add_systems(Update, (box_select_system, group_action_system).chain());
+        app
+    }
+
+    fn zone(app: &mut App, zone_type: CaseZoneType, x: f32) -> Entity {
+        let transform = Transform::from_xyz(x, 0.0, 0.0);
+        app.world_mut()
+            .spawn((
+                CaseZone {
+                    zone_type,
+                    transform,
+                    num: 0,
+                },
+                GlobalTransform::from(transform),
+            ))
+            .id()
+    }
+
+    fn card(app: &mut App, x: f32) -> Entity {
+        app.world_mut()
+            .spawn((Card::default(), GlobalTransform::from_xyz(x, 0.0, 0.0)))
+            .id()
+    }
+
+    #[derive(Resource, Default)]
+    struct Moves(Vec<(Entity, Entity)>);
+
+    fn record_move(trigger: Trigger<MoveRequested>, mut moves: ResMut<Moves>) {
+        moves.0.push((trigger.card, trigger.zone));
+    }
+
+    #[test]
+    fn box_select_marks_cards_inside() {
+        let mut app = app();
+        app.init_resource::<ManualTextureViews>()
+            .init_resource::<Assets<Image>>()
+            .add_event::<AssetEvent<Image>>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_systems(PostUpdate, camera_system::<Projection>);
+        let window = app
+            .world_mut()
+            .spawn((
+                Window {
+                    resolution: (800.0, 600.0).into(),
+                    ..default()
+                },
+                PrimaryWindow,
+            ))
+            .id();
+        app.world_mut().spawn((
+            Camera::default(),
+            Projection::Perspective(default()),
+            CameraRig::default(),
+            GlobalTransform::from(Transform::from_xyz(0.0, 0.0, 25.0)),
+        ));
+        let left = card(&mut app, -5.0);
+        let middle = card(&mut app, 0.0);
+        let right = card(&mut app, 5.0);
+        // 之前选中的卡片 不按加选键时会被清掉
+        app.world_mut().entity_mut(right).insert(Selected);
+        // 先跑一帧算出相机的视口
+        app.update();
+
+        let mouse = |app: &mut App, state: ButtonState, cursor: Vec2| {
+            app.world_mut()
+                .get_mut::<Window>(window)
+                .unwrap()
+                .set_cursor_position(Some(cursor));
+            app.world_mut().send_event(MouseButtonInput {
+                button: MouseButton::Left,
+                state,
+                window,
+            });
+            app.update();
+        };
+        mouse(&mut app, ButtonState::Pressed, Vec2::new(100.0, 100.0));
+        // 拖动中显示方框 还没有选中
+        app.world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(Some(Vec2::new(450.0, 500.0)));
+        app.update();
+        let boxes = app
+            .world_mut()
+            .query_filtered::<(), With<SelectionBoxNode>>()
+            .iter(app.world())
+            .count();
+        assert_eq!(boxes, 1);
+        assert!(!app.world().entity(left).contains::<Selected>());
+        mouse(&mut app, ButtonState::Released, Vec2::new(450.0, 500.0));
+
+        assert!(app.world().entity(left).contains::<Selected>());
+        assert!(app.world().entity(middle).contains::<Selected>());
+        assert!(!app.world().entity(right).contains::<Selected>());
+        let boxes = app
+            .world_mut()
+            .query_filtered::<(), With<SelectionBoxNode>>()
+            .iter(app.world())
+            .count();
+        assert_eq!(boxes, 0);
+    }
+
+    #[test]
+    fn group_move_picks_distinct_free_safe_zones() {
+        let mut app = app();
+        app.init_resource::<Moves>().add_observer(record_move);
+        let near = zone(&mut app, CaseZoneType::Safe, -4.0);
+        let taken = zone(&mut app, CaseZoneType::Safe, -4.1);
+        let middle = zone(&mut app, CaseZoneType::Safe, 0.0);
+        zone(&mut app, CaseZoneType::Battle, -3.9);
+        let other = card(&mut app, -4.1);
+        app.world_mut()
+            .entity_mut(other)
+            .insert(CardLocation::Zone(taken));
+        // 两张都离同一个安全区最近
+        let first = card(&mut app, -4.2);
+        let second = card(&mut app, -3.8);
+        for entity in [first, second] {
+            app.world_mut()
+                .entity_mut(entity)
+                .insert((Selected, CardLocation::Hand));
+        }
+        app.world_mut()
+            .resource_mut::<ActionState>()
+            .press(InputAction::MoveSelectedToSafe);
+        app.update();
+
+        let moves = &app.world().resource::<Moves>().0;
+        assert_eq!(moves, &vec![(first, near), (second, middle)]);
+        assert!(!app.world().entity(first).contains::<Selected>());
+        assert!(!app.world().entity(second).contains::<Selected>());
+    }
+
+    #[test]
+    fn zone_at_finds_zone_under_point() {
+        let mut app = app();
+        let battle = zone(&mut app, CaseZoneType::Battle, 0.0);
+        let safe = zone(&mut app, CaseZoneType::Safe, 6.0);
+        app.world_mut().entity_mut(safe).insert(Aabb::from_min_max(
+            Vec3::new(-1.0, -1.5, 0.0),
+            Vec3::new(1.0, 1.5, 0.0),
+        ));
+        // 空位不算场地
+        zone(&mut app, CaseZoneType::Nothing, 20.0);
+        let at = |app: &mut App, point: Vec3| {
+            app.world_mut()
+                .run_system_once(
+                    move |zones: Query<(Entity, &CaseZone, &GlobalTransform, Option<&Aabb>)>| {
+                        zone_at(point, &zones)
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(at(&mut app, Vec3::new(1.5, -1.9, 0.0)), Some(battle));
+        assert_eq!(at(&mut app, Vec3::new(6.9, 1.4, 0.0)), Some(safe));
+        assert_eq!(at(&mut app, Vec3::new(6.0, 1.6, 0.0)), None);
+        assert_eq!(at(&mut app, Vec3::new(20.0, 0.0, 0.0)), None);
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_tween::bevy_time_runner::TimeRunner;
+use bevy_tween::combinator::AnimationBuilder;
+
+// 动画的分类 每一类可以单独调整速度
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationCategory {
+    // 悬停 拖拽回手
+    Hover,
+    // 出牌 翻面 横置
+    Play,
+    // 战斗 受击
+    Combat,
+    // 特效 镜头
+    Effects,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct AnimationSettings {
+    // 全局倍速 2.0 表示所有动画快一倍
+    pub speed: f32,
+    // 减少动效 关闭镜头抖动和大幅度的缩放
+    pub reduced_motion: bool,
+    // 重要事件时镜头推过去看一眼
+    pub camera_focus: bool,
+    // 每个分类额外的倍速 会和全局倍速相乘
+    pub overrides: HashMap<AnimationCategory, f32>,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            reduced_motion: false,
+            camera_focus: true,
+            overrides: HashMap::default(),
+        }
+    }
+}
+
+impl AnimationSettings {
+    pub fn speed_of(&self, category: AnimationCategory) -> f32 {
+        self.speed * self.overrides.get(&category).copied().unwrap_or(1.0)
+    }
+
+    pub fn camera_shake(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    pub fn large_scaling(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    pub fn camera_focus(&self) -> bool {
+        self.camera_focus && !self.reduced_motion
+    }
+}
+
+pub trait AnimationCategoryExt {
+    // 给动画打上分类 插入 TimeRunner 时按设置调整倍速
+    fn category(self, category: AnimationCategory) -> Self;
+}
+
+impl AnimationCategoryExt for AnimationBuilder<'_> {
+    fn category(mut self, category: AnimationCategory) -> Self {
+        self.entity_commands().insert(category);
+        self
+    }
+}
+
+// 每次插入 TimeRunner 时乘上设置里的倍速
+// 分类只对这一次动画有效 用完就删掉 同一个实体之后不打分类的动画按全局倍速
+pub fn apply_animation_settings(
+    trigger: Trigger<OnInsert, TimeRunner>,
+    mut commands: Commands,
+    settings: Res<AnimationSettings>,
+    mut runners: Query<(&mut TimeRunner, Option<&AnimationCategory>)>,
+) {
+    let Ok((mut runner, category)) = runners.get_mut(trigger.entity()) else {
+        return;
+    };
+    let speed = match category {
+        Some(category) => {
+            commands
+                .entity(trigger.entity())
+                .remove::<AnimationCategory>();
+            settings.speed_of(*category)
+        }
+        None => settings.speed,
+    };
+    let time_scale = runner.time_scale() * speed;
+    runner.set_time_scale(time_scale);
+}
@@ -0,0 +1,495 @@
+use crate::locale::{parse_key_values, read_source};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+
+pub const THEME_PATH: &str = "ui.theme";
+
+#[derive(Debug, Clone)]
+pub struct Palette {
+    // 深色半透明底板 HUD 记录 大图
+    pub panel: Color,
+    // 当前回合玩家的面板
+    pub panel_active: Color,
+    // 浅色底板 对话框 选择
+    pub dialog: Color,
+    // 对话框后面挡住棋盘的底
+    pub backdrop: Color,
+    pub text_light: Color,
+    pub text_dark: Color,
+    pub text_muted: Color,
+    pub text_error: Color,
+    pub accent: Color,
+    pub highlight: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            panel: Color::srgba(0.0, 0.0, 0.0, 0.6),
+            panel_active: Color::srgba(0.35, 0.3, 0.0, 0.75),
+            dialog: Color::srgba(1.0, 1.0, 1.0, 0.6),
+            backdrop: Color::srgba(0.0, 0.0, 0.0, 0.3),
+            text_light: Color::WHITE,
+            text_dark: Color::BLACK,
+            text_muted: Color::srgb(0.85, 0.85, 0.85),
+            text_error: Color::srgb(0.8, 0.0, 0.0),
+            accent: Color::srgb(0.9, 0.9, 0.0),
+            highlight: Color::srgb(0.0, 0.9, 0.9),
+        }
+    }
+}
+
+// 按钮在各个状态下的底色
+#[derive(Debug, Clone, Copy)]
+pub struct ButtonColors {
+    pub normal: Color,
+    pub hovered: Color,
+    pub pressed: Color,
+    pub disabled: Color,
+    pub selected: Color,
+}
+
+impl ButtonColors {
+    pub fn color(&self, interaction: Interaction, selected: bool, disabled: bool) -> Color {
+        if disabled {
+            return self.disabled;
+        }
+        match interaction {
+            Interaction::Pressed => self.pressed,
+            Interaction::Hovered => self.hovered,
+            Interaction::None if selected => self.selected,
+            Interaction::None => self.normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ButtonTheme {
+    pub primary: ButtonColors,
+    pub danger: ButtonColors,
+    // 列表项 选中时高亮
+    pub item: ButtonColors,
+}
+
+impl Default for ButtonTheme {
+    fn default() -> Self {
+        let disabled = Color::srgb(0.3, 0.3, 0.3);
+        Self {
+            primary: ButtonColors {
+                normal: Color::srgb(0.0, 0.1, 0.1),
+                hovered: Color::srgb(0.0, 0.25, 0.25),
+                pressed: Color::srgb(0.0, 0.4, 0.4),
+                disabled,
+                selected: Color::srgb(0.0, 0.25, 0.25),
+            },
+            danger: ButtonColors {
+                normal: Color::srgb(1.0, 0.0, 0.0),
+                hovered: Color::srgb(1.0, 0.3, 0.3),
+                pressed: Color::srgb(0.6, 0.0, 0.0),
+                disabled,
+                selected: Color::srgb(1.0, 0.3, 0.3),
+            },
+            item: ButtonColors {
+                normal: Color::srgba(0.0, 0.0, 0.0, 0.2),
+                hovered: Color::srgba(0.0, 0.0, 0.0, 0.35),
+                pressed: Color::srgba(0.9, 0.9, 0.0, 0.8),
+                disabled: Color::srgba(0.0, 0.0, 0.0, 0.1),
+                selected: Color::srgba(0.9, 0.9, 0.0, 0.6),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FontSizes {
+    pub title: f32,
+    pub heading: f32,
+    pub body: f32,
+    pub small: f32,
+    pub tiny: f32,
+}
+
+impl Default for FontSizes {
+    fn default() -> Self {
+        Self {
+            title: 33.0,
+            heading: 28.0,
+            body: 24.0,
+            small: 20.0,
+            tiny: 16.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Spacing {
+    pub small: f32,
+    pub medium: f32,
+    pub large: f32,
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Self {
+            small: 4.0,
+            medium: 8.0,
+            large: 20.0,
+        }
+    }
+}
+
+// 所有界面共用的样式 可以用 assets/ui.theme 覆盖
+#[derive(Resource, Asset, TypePath, Debug, Clone)]
+pub struct UiTheme {
+    pub palette: Palette,
+    pub buttons: ButtonTheme,
+    pub fonts: FontSizes,
+    pub spacing: Spacing,
+    pub panel_radius: f32,
+    pub button_radius: f32,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        Self {
+            palette: Palette::default(),
+            buttons: ButtonTheme::default(),
+            fonts: FontSizes::default(),
+            spacing: Spacing::default(),
+            panel_radius: 10.0,
+            button_radius: 6.0,
+        }
+    }
+}
+
+impl UiTheme {
+    // 每行一个 key = value 颜色写 #rrggbb 或 #rrggbbaa 没写的保持默认值
+    pub fn parse(source: &str) -> Result<Self, std::io::Error> {
+        let mut theme = UiTheme::default();
+        for (key, value) in parse_key_values(source)? {
+            if let Some(slot) = theme.color_mut(&key) {
+                *slot = Srgba::hex(&value)
+                    .map_err(|err| invalid(&key, &value, err))?
+                    .into();
+            } else if let Some(slot) = theme.number_mut(&key) {
+                *slot = value.parse().map_err(|err| invalid(&key, &value, err))?;
+            } else {
+                warn!("未知的主题项 {}", key);
+            }
+        }
+        Ok(theme)
+    }
+
+    fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        let palette = &mut self.palette;
+        let slot = match key {
+            "palette.panel" => &mut palette.panel,
+            "palette.panel_active" => &mut palette.panel_active,
+            "palette.dialog" => &mut palette.dialog,
+            "palette.backdrop" => &mut palette.backdrop,
+            "palette.text_light" => &mut palette.text_light,
+            "palette.text_dark" => &mut palette.text_dark,
+            "palette.text_muted" => &mut palette.text_muted,
+            "palette.text_error" => &mut palette.text_error,
+            "palette.accent" => &mut palette.accent,
+            "palette.highlight" => &mut palette.highlight,
+            _ => {
+                let (group, state) = key.strip_prefix("button.")?.split_once('.')?;
+                let colors = match group {
+                    "primary" => &mut self.buttons.primary,
+                    "danger" => &mut self.buttons.danger,
+                    "item" => &mut self.buttons.item,
+                    _ => return None,
+                };
+                match state {
+                    "normal" => &mut colors.normal,
+                    "hovered" => &mut colors.hovered,
+                    "pressed" => &mut colors.pressed,
+                    "disabled" => &mut colors.disabled,
+                    "selected" => &mut colors.selected,
+                    _ => return None,
+                }
+            }
+        };
+        Some(slot)
+    }
+
+    fn number_mut(&mut self, key: &str) -> Option<&mut f32> {
+        let slot = match key {
+            "font.title" => &mut self.fonts.title,
+            "font.heading" => &mut self.fonts.heading,
+            "font.body" => &mut self.fonts.body,
+            "font.small" => &mut self.fonts.small,
+            "font.tiny" => &mut self.fonts.tiny,
+            "spacing.small" => &mut self.spacing.small,
+            "spacing.medium" => &mut self.spacing.medium,
+            "spacing.large" => &mut self.spacing.large,
+            "radius.panel" => &mut self.panel_radius,
+            "radius.button" => &mut self.button_radius,
+            _ => return None,
+        };
+        Some(slot)
+    }
+
+    pub fn panel_color(&self, role: PanelRole) -> Color {
+        match role {
+            PanelRole::Panel => self.palette.panel,
+            PanelRole::Active => self.palette.panel_active,
+            PanelRole::Dialog => self.palette.dialog,
+            PanelRole::Backdrop => self.palette.backdrop,
+        }
+    }
+
+    pub fn text_color(&self, role: TextRole) -> Color {
+        match role {
+            TextRole::Light => self.palette.text_light,
+            TextRole::Dark => self.palette.text_dark,
+            TextRole::Muted => self.palette.text_muted,
+            TextRole::Error => self.palette.text_error,
+            TextRole::Accent => self.palette.accent,
+        }
+    }
+
+    pub fn font_size(&self, role: FontRole) -> f32 {
+        match role {
+            FontRole::Title => self.fonts.title,
+            FontRole::Heading => self.fonts.heading,
+            FontRole::Body => self.fonts.body,
+            FontRole::Small => self.fonts.small,
+            FontRole::Tiny => self.fonts.tiny,
+        }
+    }
+
+    pub fn button_colors(&self, role: ButtonRole) -> &ButtonColors {
+        match role {
+            ButtonRole::Primary => &self.buttons.primary,
+            ButtonRole::Danger => &self.buttons.danger,
+            ButtonRole::Item => &self.buttons.item,
+        }
+    }
+
+    pub fn button_text(&self, role: ButtonRole) -> Color {
+        match role {
+            ButtonRole::Primary => self.palette.accent,
+            ButtonRole::Danger => self.palette.text_muted,
+            ButtonRole::Item => self.palette.text_dark,
+        }
+    }
+}
+
+fn invalid(key: &str, value: &str, err: impl std::fmt::Display) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{} = {}: {}", key, value, err),
+    )
+}
+
+#[derive(Default)]
+pub struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    type Asset = UiTheme;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        UiTheme::parse(&read_source(reader).await?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme"]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelRole {
+    Panel,
+    Active,
+    Dialog,
+    Backdrop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextRole {
+    Light,
+    Dark,
+    Muted,
+    Error,
+    Accent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontRole {
+    Title,
+    Heading,
+    Body,
+    Small,
+    Tiny,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonRole {
+    Primary,
+    Danger,
+    Item,
+}
+
+// 底板 颜色和圆角跟随主题
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemedPanel(pub PanelRole);
+
+// 文本 颜色和字号跟随主题
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemedText {
+    pub font: FontRole,
+    pub color: TextRole,
+}
+
+impl ThemedText {
+    pub fn new(font: FontRole, color: TextRole) -> Self {
+        Self { font, color }
+    }
+}
+
+// 按钮 悬停 按下 禁用 选中 都会换底色
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemedButton {
+    pub role: ButtonRole,
+    pub selected: bool,
+    pub disabled: bool,
+}
+
+impl ThemedButton {
+    pub fn new(role: ButtonRole) -> Self {
+        Self {
+            role,
+            selected: false,
+            disabled: false,
+        }
+    }
+}
+
+// 按钮文字的颜色跟随所在按钮
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemedButtonText(pub FontRole);
+
+#[derive(Resource, Debug)]
+pub struct ThemeHandle(pub Handle<UiTheme>);
+
+pub fn load_theme(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ThemeHandle(asset_server.load(THEME_PATH)));
+}
+
+// 主题文件加载或修改后替换当前主题
+pub fn reload_theme_system(
+    mut events: EventReader<AssetEvent<UiTheme>>,
+    handle: Option<Res<ThemeHandle>>,
+    themes: Res<Assets<UiTheme>>,
+    mut theme: ResMut<UiTheme>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+    let reloaded = events
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0));
+    if let Some(loaded) = themes.get(&handle.0).filter(|_| reloaded) {
+        *theme = loaded.clone();
+    }
+}
+
+pub fn apply_panel_theme(
+    theme: Res<UiTheme>,
+    mut panels: Query<(
+        Ref<ThemedPanel>,
+        &mut BackgroundColor,
+        Option<&mut BorderRadius>,
+    )>,
+) {
+    for (panel, mut background, radius) in panels.iter_mut() {
+        if !theme.is_changed() && !panel.is_changed() {
+            continue;
+        }
+        background.0 = theme.panel_color(panel.0);
+        if let Some(mut radius) = radius {
+            *radius = BorderRadius::all(Val::Px(theme.panel_radius));
+        }
+    }
+}
+
+pub fn apply_text_theme(
+    theme: Res<UiTheme>,
+    mut texts: Query<(Ref<ThemedText>, &mut TextColor, &mut TextFont)>,
+) {
+    for (text, mut color, mut font) in texts.iter_mut() {
+        if !theme.is_changed() && !text.is_changed() {
+            continue;
+        }
+        color.0 = theme.text_color(text.color);
+        font.font_size = theme.font_size(text.font);
+    }
+}
+
+// 按钮的交互反馈 文字颜色跟着按钮的种类走
+#[allow(clippy::type_complexity)]
+pub fn button_feedback_system(
+    theme: Res<UiTheme>,
+    mut buttons: Query<(
+        Ref<Interaction>,
+        Ref<ThemedButton>,
+        &mut BackgroundColor,
+        Option<&mut BorderRadius>,
+        &Children,
+    )>,
+    mut texts: Query<(&ThemedButtonText, &mut TextColor, &mut TextFont)>,
+) {
+    for (interaction, button, mut background, radius, children) in buttons.iter_mut() {
+        let refresh = theme.is_changed() || button.is_changed();
+        if !refresh && !interaction.is_changed() {
+            continue;
+        }
+        let colors = theme.button_colors(button.role);
+        background.0 = colors.color(*interaction, button.selected, button.disabled);
+        if !refresh {
+            continue;
+        }
+        if let Some(mut radius) = radius {
+            *radius = BorderRadius::all(Val::Px(theme.button_radius));
+        }
+        let mut iter = texts.iter_many_mut(children);
+        while let Some((text, mut color, mut font)) = iter.fetch_next() {
+            color.0 = if button.disabled {
+                theme.palette.text_muted
+            } else {
+                theme.button_text(button.role)
+            };
+            font.font_size = theme.font_size(text.0);
+        }
+    }
+}
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<UiTheme>()
+            .init_asset_loader::<ThemeLoader>()
+            .init_resource::<UiTheme>()
+            .add_systems(Startup, load_theme)
+            .add_systems(
+                PostUpdate,
+                (
+                    reload_theme_system,
+                    (apply_panel_theme, apply_text_theme, button_feedback_system),
+                )
+                    .chain()
+                    .before(bevy::ui::UiSystem::Prepare),
+            );
+    }
+}
@@ -0,0 +1,334 @@
+use crate::camera_controller::CameraController;
+use crate::camera_rig::CameraRig;
+use crate::cards::Dragging;
+use crate::inspect::HoverInspect;
+use bevy::input::touch::Touch;
+use bevy::picking::pointer::PointerId;
+use bevy::prelude::*;
+
+// 触屏手势的参数
+#[derive(Resource, Debug, Clone)]
+pub struct TouchSettings {
+    // 按住多久算长按
+    pub long_press_secs: f32,
+    // 手指移动超过这么多像素就不算长按和点击
+    pub move_tolerance: f32,
+    // 双指张开一个像素拉近的比例
+    pub pinch_speed: f32,
+    // 双指平移一个像素移动的距离
+    pub pan_speed: f32,
+}
+
+impl Default for TouchSettings {
+    fn default() -> Self {
+        Self {
+            long_press_secs: 0.5,
+            move_tolerance: 12.0,
+            pinch_speed: 0.02,
+            pan_speed: 0.02,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TouchPress {
+    card: Entity,
+    touch: u64,
+    elapsed: f32,
+    // 已经当作长按处理了 松手时不再算点击
+    fired: bool,
+}
+
+// 正在按着的卡片 由 cards.rs 里的 Pointer<Down> 观察者记录
+#[derive(Resource, Debug, Default)]
+pub struct TouchGestures {
+    press: Option<TouchPress>,
+}
+
+impl TouchGestures {
+    pub fn begin(&mut self, card: Entity, pointer: PointerId) {
+        if let PointerId::Touch(touch) = pointer {
+            self.press = Some(TouchPress {
+                card,
+                touch,
+                elapsed: 0.0,
+                fired: false,
+            });
+        }
+    }
+
+    // 这次按下是不是已经变成了长按
+    pub fn was_long_press(&self, card: Entity) -> bool {
+        self.press
+            .is_some_and(|press| press.card == card && press.fired)
+    }
+}
+
+fn moved(touch: &Touch, settings: &TouchSettings) -> bool {
+    touch.position().distance(touch.start_position()) > settings.move_tolerance
+}
+
+// 按住不动超过时间就显示大图 移动或开始拖拽就取消
+pub fn long_press_system(
+    time: Res<Time>,
+    touches: Res<Touches>,
+    settings: Res<TouchSettings>,
+    mut gestures: ResMut<TouchGestures>,
+    mut inspect: ResMut<HoverInspect>,
+    dragging: Query<(), With<Dragging>>,
+) {
+    let Some(press) = gestures.press.as_mut() else {
+        return;
+    };
+    // 松手后保留到下一次按下 点击的观察者还要查询
+    if touches.just_released(press.touch) || touches.just_canceled(press.touch) {
+        return;
+    }
+    let Some(touch) = touches.get_pressed(press.touch) else {
+        gestures.press = None;
+        return;
+    };
+    if press.fired {
+        return;
+    }
+    if moved(touch, &settings) || dragging.contains(press.card) {
+        gestures.press = None;
+        return;
+    }
+    press.elapsed += time.delta_secs();
+    if press.elapsed >= settings.long_press_secs {
+        press.fired = true;
+        inspect.show_now(press.card);
+    }
+}
+
+// 两根手指时移动镜头 张开拉近 一起移动平移 拖拽卡片时不动
+pub fn touch_camera_system(
+    touches: Res<Touches>,
+    settings: Res<TouchSettings>,
+    dragging: Query<(), With<Dragging>>,
+    mut rigs: Query<(&mut CameraRig, Option<&CameraController>)>,
+) {
+    let pressed: Vec<&Touch> = touches.iter().collect();
+    let [a, b] = pressed.as_slice() else {
+        return;
+    };
+    if !dragging.is_empty() {
+        return;
+    }
+    let center = (a.position() + b.position()) / 2.0;
+    let previous_center = (a.previous_position() + b.previous_position()) / 2.0;
+    let pan = center - previous_center;
+    let pinch =
+        a.position().distance(b.position()) - a.previous_position().distance(b.previous_position());
+    if pan == Vec2::ZERO && pinch == 0.0 {
+        return;
+    }
+    for (mut rig, controller) in rigs.iter_mut() {
+        if controller.is_some_and(|controller| controller.enabled) {
+            continue;
+        }
+        // 和右键拖动一样 棋盘跟着手指走 屏幕的 y 朝下
+        rig.pan(Vec2::new(-pan.x, pan.y) * settings.pan_speed);
+        if pinch != 0.0 {
+            rig.zoom(pinch * settings.pinch_speed);
+        }
+    }
+}
+
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchSettings>()
+            .init_resource::<TouchGestures>()
+            .add_systems(Update, (long_press_system, touch_camera_system));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{press_card, tap_card, Card, CardInfo};
+    use crate::focus::FocusCursor;
+    use crate::presentation::PresentationQueue;
+    use bevy::input::touch::{TouchInput, TouchPhase};
+    use bevy::input::InputPlugin;
+    use bevy::picking::backend::HitData;
+    use bevy::picking::pointer::{Location, PointerButton};
+    use bevy::render::camera::NormalizedRenderTarget;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    struct Harness {
+        app: App,
+        window: Entity,
+        card: Entity,
+        face: Entity,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let mut app = App::new();
+            app.add_plugins((MinimalPlugins, InputPlugin, TouchPlugin))
+                .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                    100,
+                )))
+                .init_resource::<HoverInspect>()
+                .init_resource::<PresentationQueue>()
+                .init_resource::<FocusCursor>();
+            let window = app.world_mut().spawn_empty().id();
+            let card = app.world_mut().spawn(Card::default()).id();
+            let face = app
+                .world_mut()
+                .spawn(CardInfo {
+                    image: Handle::default(),
+                })
+                .set_parent(card)
+                .observe(press_card)
+                .observe(tap_card)
+                .id();
+            // 第一帧的时间差是零
+            app.update();
+            Self {
+                app,
+                window,
+                card,
+                face,
+            }
+        }
+
+        fn touch(&mut self, id: u64, phase: TouchPhase, position: Vec2) {
+            let window = self.window;
+            self.app.world_mut().send_event(TouchInput {
+                phase,
+                position,
+                window,
+                force: None,
+                id,
+            });
+        }
+
+        // 拾取后端会在卡面上发出的指针事件
+        fn pointer<E: std::fmt::Debug + Clone + Reflect>(&mut self, id: u64, event: E) {
+            let location = Location {
+                target: NormalizedRenderTarget::Image(Handle::default()),
+                position: Vec2::ZERO,
+            };
+            let face = self.face;
+            self.app.world_mut().trigger_targets(
+                Pointer::new(face, PointerId::Touch(id), location, event),
+                face,
+            );
+        }
+
+        fn hit(&self) -> HitData {
+            HitData::new(self.window, 0.0, None, None)
+        }
+
+        fn down(&mut self, id: u64, position: Vec2) {
+            self.touch(id, TouchPhase::Started, position);
+            let hit = self.hit();
+            self.pointer(
+                id,
+                Down {
+                    button: PointerButton::Primary,
+                    hit,
+                },
+            );
+            self.app.update();
+        }
+
+        fn up(&mut self, id: u64, position: Vec2) {
+            self.touch(id, TouchPhase::Ended, position);
+            self.app.update();
+            let hit = self.hit();
+            self.pointer(
+                id,
+                Click {
+                    button: PointerButton::Primary,
+                    hit,
+                    duration: Duration::ZERO,
+                },
+            );
+            self.app.update();
+        }
+
+        fn cursor(&self) -> &FocusCursor {
+            self.app.world().resource::<FocusCursor>()
+        }
+
+        fn inspected(&self) -> Option<Entity> {
+            self.app.world().resource::<HoverInspect>().card()
+        }
+    }
+
+    #[test]
+    fn tap_holds_the_card() {
+        let mut harness = Harness::new();
+        harness.down(1, Vec2::new(100.0, 100.0));
+        harness.up(1, Vec2::new(100.0, 100.0));
+        assert_eq!(harness.cursor().held, Some(harness.card));
+        assert_eq!(harness.inspected(), None);
+
+        // 再点一次取消
+        harness.down(2, Vec2::new(100.0, 100.0));
+        harness.up(2, Vec2::new(100.0, 100.0));
+        assert_eq!(harness.cursor().held, None);
+    }
+
+    #[test]
+    fn long_press_inspects_without_tapping() {
+        let mut harness = Harness::new();
+        harness.down(1, Vec2::new(100.0, 100.0));
+        for _ in 0..3 {
+            harness.app.update();
+        }
+        assert_eq!(harness.inspected(), None);
+        for _ in 0..3 {
+            harness.app.update();
+        }
+        assert_eq!(harness.inspected(), Some(harness.card));
+        assert!(harness
+            .app
+            .world()
+            .resource::<TouchGestures>()
+            .was_long_press(harness.card));
+
+        // 松手不算点击
+        harness.up(1, Vec2::new(100.0, 100.0));
+        assert_eq!(harness.cursor().held, None);
+    }
+
+    #[test]
+    fn moving_finger_cancels_long_press() {
+        let mut harness = Harness::new();
+        harness.down(1, Vec2::new(100.0, 100.0));
+        harness.touch(1, TouchPhase::Moved, Vec2::new(140.0, 100.0));
+        for _ in 0..8 {
+            harness.app.update();
+        }
+        assert_eq!(harness.inspected(), None);
+    }
+
+    #[test]
+    fn two_fingers_pan_and_pinch_the_camera() {
+        let mut harness = Harness::new();
+        let rig = harness.app.world_mut().spawn(CameraRig::default()).id();
+        let pose = |harness: &Harness| harness.app.world().get::<CameraRig>(rig).unwrap().pose;
+        let start = pose(&harness);
+
+        harness.touch(1, TouchPhase::Started, Vec2::new(100.0, 100.0));
+        harness.touch(2, TouchPhase::Started, Vec2::new(200.0, 100.0));
+        harness.app.update();
+        // 两根手指一起往下移 同时张开
+        harness.touch(1, TouchPhase::Moved, Vec2::new(90.0, 120.0));
+        harness.touch(2, TouchPhase::Moved, Vec2::new(210.0, 120.0));
+        harness.app.update();
+
+        let end = pose(&harness);
+        assert!(end.distance < start.distance);
+        assert!(end.focus.y > start.focus.y);
+        assert_eq!(end.focus.x, start.focus.x);
+    }
+}